@@ -62,6 +62,12 @@ pub enum CredentialError {
     #[msg("Validity period exceeds maximum")]
     ValidityPeriodTooLong,
 
+    #[msg("Holder identity is frozen or deactivated")]
+    HolderIdentityNotActive,
+
+    #[msg("Holder requires a live consent grant for this verifier")]
+    ConsentRequired,
+
     #[msg("Arithmetic overflow")]
     Overflow,
 }
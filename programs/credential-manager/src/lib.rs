@@ -5,6 +5,10 @@ pub mod errors;
 
 use state::*;
 use errors::*;
+use identity_registry::state::{
+    consent_scopes, delegate_permissions, org_roles, ConsentGrant, Delegate, IdentityAccount,
+    IdentityStatus, IdentityType, OrgMember,
+};
 
 declare_id!("Fib1drk4v1pTPFxVZbvkuFxEUiZ8vXZNJuRq97YUdaG4");
 
@@ -84,6 +88,14 @@ pub mod credential_manager {
         verification_level: u8,
     ) -> Result<()> {
         require!(name.len() <= CredentialIssuer::MAX_NAME_LEN, CredentialError::SchemaNameTooLong);
+        require!(
+            is_issuer_operator(
+                &ctx.accounts.identity,
+                &ctx.accounts.authority.key(),
+                ctx.accounts.org_member.as_deref(),
+            ),
+            CredentialError::UnauthorizedIssuer
+        );
 
         let issuer = &mut ctx.accounts.issuer;
         let clock = Clock::get()?;
@@ -120,6 +132,14 @@ pub mod credential_manager {
         // Validate
         require!(schema.active, CredentialError::SchemaNotActive);
         require!(issuer.active, CredentialError::IssuerNotActive);
+        require!(
+            is_issuer_operator(
+                &ctx.accounts.issuer_identity,
+                &issuer.authority,
+                ctx.accounts.org_member.as_deref(),
+            ),
+            CredentialError::UnauthorizedIssuer
+        );
         require!(
             issuer.verification_level >= schema.required_issuer_verification,
             CredentialError::InsufficientIssuerVerification
@@ -265,8 +285,22 @@ pub mod credential_manager {
     /// Verify a credential is valid
     pub fn verify_credential(ctx: Context<VerifyCredential>) -> Result<()> {
         let credential = &ctx.accounts.credential;
+        let holder_identity = &ctx.accounts.holder_identity;
         let clock = Clock::get()?;
 
+        // Holders in strict consent mode must have let the verifier check their credentials
+        if holder_identity.strict_consent {
+            require!(
+                ctx.accounts.consent.as_ref().is_some_and(|consent| consent.allows(
+                    &holder_identity.key(),
+                    &ctx.accounts.verifier.key(),
+                    consent_scopes::CREDENTIAL_VERIFICATION,
+                    clock.unix_timestamp,
+                )),
+                CredentialError::ConsentRequired
+            );
+        }
+
         // Check status
         let is_valid = match credential.status {
             CredentialStatus::Active => {
@@ -286,6 +320,39 @@ pub mod credential_manager {
         Ok(())
     }
 
+    /// Present a credential on behalf of its holder identity. Succeeds only if the
+    /// credential is currently valid and the signer is the holder's authority or a
+    /// delegate allowed to present credentials.
+    pub fn present_credential(ctx: Context<PresentCredential>) -> Result<()> {
+        let credential = &ctx.accounts.credential;
+        let holder_identity = &ctx.accounts.holder_identity;
+        let clock = Clock::get()?;
+
+        require!(
+            holder_identity.is_authorized(
+                &holder_identity.key(),
+                &ctx.accounts.presenter.key(),
+                ctx.accounts.delegate.as_deref(),
+                delegate_permissions::PRESENT_CREDENTIALS,
+                clock.unix_timestamp,
+            ),
+            CredentialError::UnauthorizedHolder
+        );
+        require!(
+            credential.status == CredentialStatus::Active,
+            CredentialError::CredentialNotActive
+        );
+        require!(
+            credential.expires_at == 0 || clock.unix_timestamp <= credential.expires_at,
+            CredentialError::CredentialExpired
+        );
+
+        msg!("Credential {:?} presented by {} for holder {}",
+            credential.credential_id, ctx.accounts.presenter.key(), credential.holder);
+
+        Ok(())
+    }
+
     /// Deactivate a schema (admin only)
     pub fn deactivate_schema(ctx: Context<DeactivateSchema>) -> Result<()> {
         ctx.accounts.schema.active = false;
@@ -333,6 +400,24 @@ fn is_identity_derived_schema(name: &str) -> bool {
 
 // ============== Account Contexts ==============
 
+/// An individual issues as its own controller; an organization through roster
+/// members holding ISSUER_OPERATOR
+fn is_issuer_operator(
+    identity: &Account<IdentityAccount>,
+    signer: &Pubkey,
+    membership: Option<&OrgMember>,
+) -> bool {
+    match identity.identity_type {
+        IdentityType::Organization => identity.has_org_role(
+            &identity.key(),
+            signer,
+            membership,
+            org_roles::ISSUER_OPERATOR,
+        ),
+        _ => identity.controller() == *signer,
+    }
+}
+
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(
@@ -389,8 +474,11 @@ pub struct RegisterIssuer<'info> {
     )]
     pub issuer: Account<'info, CredentialIssuer>,
 
-    /// CHECK: Identity account of the issuer
-    pub identity: AccountInfo<'info>,
+    /// Identity the issuer acts for, an individual or an organization
+    pub identity: Account<'info, IdentityAccount>,
+
+    /// Signer's roster entry when `identity` is an organization
+    pub org_member: Option<Account<'info, OrgMember>>,
 
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -434,6 +522,12 @@ pub struct IssueCredential<'info> {
     /// CHECK: Holder's identity account
     pub holder: AccountInfo<'info>,
 
+    #[account(address = issuer.identity)]
+    pub issuer_identity: Account<'info, IdentityAccount>,
+
+    /// Issuer's roster entry when issuing for an organization
+    pub org_member: Option<Account<'info, OrgMember>>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
@@ -519,11 +613,16 @@ pub struct TransferCredential<'info> {
         mut,
         seeds = [b"credential", credential.credential_id.as_ref()],
         bump = credential.bump,
-        constraint = credential.holder == holder.key()
+        constraint = credential.holder == holder_identity.key() @ CredentialError::UnauthorizedHolder
     )]
     pub credential: Account<'info, Credential>,
 
-    /// Current holder must sign
+    /// Identity currently holding the credential
+    #[account(constraint = holder_identity.status == IdentityStatus::Active @ CredentialError::HolderIdentityNotActive)]
+    pub holder_identity: Account<'info, IdentityAccount>,
+
+    /// Controller of the holder identity (its guardian while one is set) must sign
+    #[account(constraint = holder_identity.controller() == holder.key() @ CredentialError::UnauthorizedHolder)]
     pub holder: Signer<'info>,
 
     /// CHECK: New holder's identity
@@ -538,9 +637,37 @@ pub struct VerifyCredential<'info> {
     )]
     pub credential: Account<'info, Credential>,
 
+    #[account(
+        address = credential.holder,
+        constraint = holder_identity.status != IdentityStatus::Deactivated @ CredentialError::HolderIdentityNotActive
+    )]
+    pub holder_identity: Account<'info, IdentityAccount>,
+
+    /// Holder's consent for the verifier, required in strict consent mode
+    pub consent: Option<Account<'info, ConsentGrant>>,
+
     pub verifier: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct PresentCredential<'info> {
+    #[account(
+        seeds = [b"credential", credential.credential_id.as_ref()],
+        bump = credential.bump,
+        constraint = credential.holder == holder_identity.key() @ CredentialError::UnauthorizedHolder
+    )]
+    pub credential: Account<'info, Credential>,
+
+    /// Holder's identity account in identity registry; frozen or deactivated holders cannot present
+    #[account(constraint = holder_identity.status == IdentityStatus::Active @ CredentialError::HolderIdentityNotActive)]
+    pub holder_identity: Account<'info, IdentityAccount>,
+
+    /// Holder identity authority, or a delegate holding PRESENT_CREDENTIALS
+    pub presenter: Signer<'info>,
+
+    pub delegate: Option<Account<'info, Delegate>>,
+}
+
 #[derive(Accounts)]
 pub struct DeactivateSchema<'info> {
     #[account(
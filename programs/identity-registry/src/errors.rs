@@ -31,4 +31,229 @@ pub enum IdentityError {
 
     #[msg("Unauthorized: Only staking manager can update staked amount")]
     UnauthorizedStakingManager,
+
+    #[msg("Recovery threshold must be between 1 and the number of recovery keys")]
+    InvalidRecoveryThreshold,
+
+    #[msg("Recovery key has already approved this proposal")]
+    RecoveryAlreadyApproved,
+
+    #[msg("Recovery delay has not elapsed")]
+    RecoveryDelayNotElapsed,
+
+    #[msg("Not enough recovery key approvals")]
+    InsufficientRecoveryApprovals,
+
+    #[msg("Only the authority of a legacy identity can migrate it")]
+    UnauthorizedMigration,
+
+    #[msg("Recovery key not found")]
+    RecoveryKeyNotFound,
+
+    #[msg("Recovery key already registered")]
+    RecoveryKeyAlreadyExists,
+
+    #[msg("Recovery key is still in its cooldown period")]
+    RecoveryKeyNotActive,
+
+    #[msg("No authority transfer pending for this signer")]
+    NoPendingAuthorityTransfer,
+
+    #[msg("DID does not match the identity being migrated")]
+    DIDMismatch,
+
+    #[msg("DID document entry field exceeds maximum length")]
+    DocumentFieldTooLong,
+
+    #[msg("Invalid verification method purposes")]
+    InvalidVerificationMethodPurpose,
+
+    #[msg("Too many verification methods")]
+    TooManyVerificationMethods,
+
+    #[msg("Verification method already exists")]
+    VerificationMethodAlreadyExists,
+
+    #[msg("Verification method not found")]
+    VerificationMethodNotFound,
+
+    #[msg("Too many service endpoints")]
+    TooManyServices,
+
+    #[msg("Service endpoint already exists")]
+    ServiceAlreadyExists,
+
+    #[msg("Service endpoint not found")]
+    ServiceNotFound,
+
+    #[msg("Identity is frozen")]
+    IdentityFrozen,
+
+    #[msg("Identity has been deactivated")]
+    IdentityDeactivated,
+
+    #[msg("Identity has not been deactivated")]
+    IdentityNotDeactivated,
+
+    #[msg("Tombstone retention period has not elapsed")]
+    TombstonePeriodNotElapsed,
+
+    #[msg("Invalid configuration value")]
+    InvalidConfigValue,
+
+    #[msg("Config does not use the pre-upgrade layout")]
+    NotLegacyConfig,
+
+    #[msg("No admin transfer pending for this signer")]
+    NoPendingAdminTransfer,
+
+    #[msg("Verification has not expired")]
+    VerificationNotExpired,
+
+    #[msg("Each required verification must be backed by its verification record")]
+    MissingVerificationRecord,
+
+    #[msg("Required verification has expired")]
+    VerificationExpired,
+
+    #[msg("Identity is missing a required verification")]
+    MissingRequiredVerification,
+
+    #[msg("Insufficient reputation score")]
+    InsufficientReputation,
+
+    #[msg("Invalid delegate permissions")]
+    InvalidDelegatePermissions,
+
+    #[msg("Delegate expiry must be in the future")]
+    InvalidDelegateExpiry,
+
+    #[msg("Signer is neither the authority nor a delegate with the required permission")]
+    UnauthorizedSigner,
+
+    #[msg("Payer must be the authority unless a sponsor account is supplied")]
+    UnauthorizedPayer,
+
+    #[msg("Sponsor is not active")]
+    SponsorInactive,
+
+    #[msg("Sponsor budget exceeded")]
+    SponsorBudgetExceeded,
+
+    #[msg("Sponsor rate limit reached for the current period")]
+    SponsorRateLimited,
+
+    #[msg("Rent recipient does not match the identity sponsor or authority")]
+    InvalidRentRecipient,
+
+    #[msg("Aadhaar verification requires a nullifier")]
+    MissingNullifier,
+
+    #[msg("Aadhaar nullifier is already claimed by another identity")]
+    NullifierAlreadyClaimed,
+
+    #[msg("Nullifier can only be released once its identity is tombstoned")]
+    NullifierIdentityActive,
+
+    #[msg("Source request was not issued by the oracle for this identity")]
+    SourceRequestMismatch,
+
+    #[msg("Wallet is already the identity authority")]
+    WalletIsAuthority,
+
+    #[msg("Signer is neither the linked wallet nor the identity authority")]
+    UnauthorizedUnlink,
+
+    #[msg("Operation not supported for this identity type")]
+    InvalidIdentityType,
+
+    #[msg("Guardian must differ from the identity authority")]
+    InvalidGuardian,
+
+    #[msg("Handover time must be in the future")]
+    InvalidHandoverTime,
+
+    #[msg("Identity has no guardian")]
+    NoGuardian,
+
+    #[msg("Guardian handover time has not been reached")]
+    HandoverNotReached,
+
+    #[msg("Authority of a dependent identity cannot change while a guardian is in control")]
+    GuardianControlled,
+
+    #[msg("Invalid organization roles")]
+    InvalidOrgRoles,
+
+    #[msg("Signer does not hold the required organization role")]
+    MissingOrgRole,
+
+    #[msg("Multisig signers must be unique and between 1 and 10")]
+    InvalidMultisigSigners,
+
+    #[msg("Multisig threshold must be between 1 and the number of signers")]
+    InvalidMultisigThreshold,
+
+    #[msg("Signer is not part of the multisig")]
+    NotMultisigSigner,
+
+    #[msg("Multisig action already approved by this signer")]
+    MultisigActionAlreadyApproved,
+
+    #[msg("Instruction does not match the approved multisig action")]
+    MultisigActionMismatch,
+
+    #[msg("Not enough multisig approvals")]
+    InsufficientMultisigApprovals,
+
+    #[msg("Multisig can only invoke the identity registry or credential manager")]
+    InvalidMultisigTarget,
+
+    #[msg("Only the admin or compliance officer can perform this action")]
+    UnauthorizedComplianceOfficer,
+
+    #[msg("Identity is not frozen")]
+    IdentityNotFrozen,
+
+    #[msg("Inactivity threshold is below the minimum")]
+    InvalidInactivityThreshold,
+
+    #[msg("Signer is not the identity nominee")]
+    NotNominee,
+
+    #[msg("Identity has not been inactive for long enough")]
+    IdentityNotInactive,
+
+    #[msg("Succession claim already in progress")]
+    SuccessionAlreadyStarted,
+
+    #[msg("No succession claim in progress")]
+    NoSuccessionClaim,
+
+    #[msg("Succession challenge window has not elapsed")]
+    ChallengeWindowNotElapsed,
+
+    #[msg("Succession claim was cancelled by owner activity")]
+    SuccessionCancelled,
+
+    #[msg("Pseudonym attestation is older than allowed")]
+    PseudonymStale,
+
+    #[msg("Signer is not the configured pseudonym attester")]
+    UnauthorizedAttester,
+
+    #[msg("Attestation expiry must be in the future and within the maximum attestation period")]
+    InvalidAttestationExpiry,
+
+    #[msg("Pseudonym attestation has expired")]
+    PseudonymAttestationExpired,
+
+    #[msg("Consent expiry must be in the future")]
+    InvalidConsentExpiry,
+
+    #[msg("Identity requires a live consent grant from the relying party")]
+    ConsentRequired,
+
+    #[msg("Arithmetic overflow")]
+    Overflow,
 }
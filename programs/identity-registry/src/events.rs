@@ -0,0 +1,152 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub verification_oracle: Pubkey,
+    pub credential_manager: Pubkey,
+    pub reputation_engine: Pubkey,
+    pub staking_manager: Pubkey,
+    pub min_stake_amount: u64,
+    pub verification_fee: u64,
+    pub tombstone_period: i64,
+    pub compliance_officer: Pubkey,
+    pub pseudonym_attester: Pubkey,
+}
+
+#[event]
+pub struct AdminTransferProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminTransferred {
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[event]
+pub struct VerificationValidityUpdated {
+    pub verification_type: u8,
+    pub validity_period: i64,
+}
+
+#[event]
+pub struct SponsorUpdated {
+    pub sponsor: Pubkey,
+    pub budget: u64,
+    pub spent: u64,
+    pub rate_limit: u32,
+    pub rate_period: i64,
+    pub active: bool,
+}
+
+#[event]
+pub struct NullifierReleased {
+    pub nullifier: [u8; 32],
+    pub identity: Pubkey,
+    pub released_by: Pubkey,
+}
+
+#[event]
+pub struct WalletLinked {
+    pub identity: Pubkey,
+    pub wallet: Pubkey,
+    pub inherits_verification: bool,
+}
+
+#[event]
+pub struct WalletUnlinked {
+    pub identity: Pubkey,
+    pub wallet: Pubkey,
+    pub unlinked_by: Pubkey,
+}
+
+#[event]
+pub struct GuardianAssigned {
+    pub identity: Pubkey,
+    pub guardian: Pubkey,
+    pub guardian_until: i64,
+}
+
+#[event]
+pub struct GuardianReleased {
+    pub identity: Pubkey,
+    pub guardian: Pubkey,
+}
+
+#[event]
+pub struct OrganizationRegistered {
+    pub identity: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct OrgMemberUpdated {
+    pub organization: Pubkey,
+    pub member: Pubkey,
+    /// 0 once the member has been removed
+    pub roles: u8,
+    pub updated_by: Pubkey,
+}
+
+#[event]
+pub struct MultisigUpdated {
+    pub identity: Pubkey,
+    pub multisig_signer: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct MultisigExecuted {
+    pub identity: Pubkey,
+    pub program_id: Pubkey,
+    pub instruction_hash: [u8; 32],
+    pub approvals: u8,
+}
+
+#[event]
+pub struct IdentityFrozen {
+    pub identity: Pubkey,
+    pub reason_code: u16,
+    pub case_reference: [u8; 32],
+    pub frozen_by: Pubkey,
+}
+
+#[event]
+pub struct IdentityUnfrozen {
+    pub identity: Pubkey,
+    pub case_reference: [u8; 32],
+    pub unfrozen_by: Pubkey,
+}
+
+#[event]
+pub struct SuccessionStarted {
+    pub identity: Pubkey,
+    pub nominee: Pubkey,
+    pub challenge_ends_at: i64,
+}
+
+#[event]
+pub struct SuccessionCompleted {
+    pub identity: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct ConsentGranted {
+    pub identity: Pubkey,
+    pub relying_party: Pubkey,
+    pub verification_mask: u64,
+    pub purpose: u16,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct ConsentRevoked {
+    pub identity: Pubkey,
+    pub relying_party: Pubkey,
+}
@@ -1,10 +1,15 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::system_program;
 
 pub mod state;
 pub mod errors;
+pub mod events;
 
 use state::*;
 use errors::*;
+use events::*;
 
 declare_id!("DPW1Ji3XhNb4zAnL9SLq5ZBjmG7ePPegWuocY5VeJLdm");
 
@@ -18,7 +23,10 @@ pub mod identity_registry {
         credential_manager: Pubkey,
         reputation_engine: Pubkey,
         staking_manager: Pubkey,
+        tombstone_period: i64,
     ) -> Result<()> {
+        require!(tombstone_period >= 0, errors::IdentityError::InvalidConfigValue);
+
         let config = &mut ctx.accounts.config;
         config.admin = ctx.accounts.admin.key();
         config.verification_oracle = verification_oracle;
@@ -27,23 +35,251 @@ pub mod identity_registry {
         config.staking_manager = staking_manager;
         config.min_stake_amount = 1_000_000_000; // 1 SOL
         config.verification_fee = 10_000_000; // 0.01 SOL
+        config.tombstone_period = tombstone_period;
+        config.pending_admin = None;
+        config.verification_validity = [0; GlobalConfig::EXPIRING_VERIFICATION_TYPES];
+        config.compliance_officer = Pubkey::default();
+        config.pseudonym_attester = Pubkey::default();
+        Ok(())
+    }
+
+    /// Update registry configuration; fields left as None are unchanged (admin only)
+    pub fn update_config(ctx: Context<UpdateConfig>, update: ConfigUpdate) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        if let Some(v) = update.verification_oracle {
+            config.verification_oracle = v;
+        }
+        if let Some(v) = update.credential_manager {
+            config.credential_manager = v;
+        }
+        if let Some(v) = update.reputation_engine {
+            config.reputation_engine = v;
+        }
+        if let Some(v) = update.staking_manager {
+            config.staking_manager = v;
+        }
+        if let Some(v) = update.min_stake_amount {
+            config.min_stake_amount = v;
+        }
+        if let Some(v) = update.verification_fee {
+            config.verification_fee = v;
+        }
+        if let Some(v) = update.tombstone_period {
+            require!(v >= 0, errors::IdentityError::InvalidConfigValue);
+            config.tombstone_period = v;
+        }
+        if let Some(v) = update.compliance_officer {
+            config.compliance_officer = v;
+        }
+        if let Some(v) = update.pseudonym_attester {
+            config.pseudonym_attester = v;
+        }
+
+        emit!(ConfigUpdated {
+            admin: config.admin,
+            verification_oracle: config.verification_oracle,
+            credential_manager: config.credential_manager,
+            reputation_engine: config.reputation_engine,
+            staking_manager: config.staking_manager,
+            min_stake_amount: config.min_stake_amount,
+            verification_fee: config.verification_fee,
+            tombstone_period: config.tombstone_period,
+            compliance_officer: config.compliance_officer,
+            pseudonym_attester: config.pseudonym_attester,
+        });
+
+        msg!("Identity registry config updated");
+
+        Ok(())
+    }
+
+    /// Propose a new admin, who must accept before taking over (admin only)
+    pub fn transfer_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.pending_admin = Some(new_admin);
+
+        emit!(AdminTransferProposed {
+            admin: config.admin,
+            pending_admin: new_admin,
+        });
+
+        Ok(())
+    }
+
+    /// Set how long verifications of a given type stay valid, 0 = forever (admin only)
+    pub fn set_verification_validity(
+        ctx: Context<UpdateConfig>,
+        verification_type: u8,
+        validity_period: i64,
+    ) -> Result<()> {
+        require!(
+            (verification_type as usize) < GlobalConfig::EXPIRING_VERIFICATION_TYPES,
+            errors::IdentityError::InvalidVerificationType
+        );
+        require!(validity_period >= 0, errors::IdentityError::InvalidConfigValue);
+
+        let config = &mut ctx.accounts.config;
+        config.verification_validity[verification_type as usize] = validity_period;
+
+        emit!(VerificationValidityUpdated {
+            verification_type,
+            validity_period,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw a pending admin transfer (admin only)
+    pub fn cancel_admin_transfer(ctx: Context<UpdateConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.pending_admin.is_some(),
+            errors::IdentityError::NoPendingAdminTransfer
+        );
+        config.pending_admin = None;
+
+        msg!("Admin transfer cancelled");
+
+        Ok(())
+    }
+
+    /// Complete a pending admin transfer, signed by the new admin
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let old_admin = config.admin;
+
+        config.admin = ctx.accounts.new_admin.key();
+        config.pending_admin = None;
+
+        emit!(AdminTransferred {
+            old_admin,
+            new_admin: config.admin,
+        });
+
+        Ok(())
+    }
+
+    /// Grow a config created before tombstones to the current layout. The appended fields
+    /// are zero-filled: no tombstone delay, no pending admin, verifications that never expire,
+    /// and no compliance officer or pseudonym attester. The payer covers the extra rent.
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let config_info = ctx.accounts.config.to_account_info();
+        {
+            let data = config_info.try_borrow_data()?;
+            require!(
+                data.len() == 8 + GlobalConfig::LEGACY_LEN && &data[..8] == GlobalConfig::DISCRIMINATOR,
+                errors::IdentityError::NotLegacyConfig
+            );
+        }
+
+        let space = 8 + GlobalConfig::LEN;
+        let required = Rent::get()?.minimum_balance(space);
+        let top_up = required.saturating_sub(config_info.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: config_info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        config_info.resize(space)?;
+
+        msg!("Registry config migrated");
+
+        Ok(())
+    }
+
+    /// Approve or update a sponsor allowed to pay rent for new identities (admin only)
+    pub fn set_sponsor(
+        ctx: Context<SetSponsor>,
+        sponsor: Pubkey,
+        budget: u64,
+        rate_limit: u32,
+        rate_period: i64,
+        active: bool,
+    ) -> Result<()> {
+        require!(rate_period >= 0, errors::IdentityError::InvalidConfigValue);
+        require!(
+            rate_limit == 0 || rate_period > 0,
+            errors::IdentityError::InvalidConfigValue
+        );
+
+        let sponsor_account = &mut ctx.accounts.sponsor_account;
+        sponsor_account.sponsor = sponsor;
+        sponsor_account.budget = budget;
+        sponsor_account.rate_limit = rate_limit;
+        sponsor_account.rate_period = rate_period;
+        sponsor_account.active = active;
+        sponsor_account.bump = ctx.bumps.sponsor_account;
+
+        emit!(SponsorUpdated {
+            sponsor,
+            budget,
+            spent: sponsor_account.spent,
+            rate_limit,
+            rate_period,
+            active,
+        });
+
         Ok(())
     }
 
+    /// Create an identity; rent is paid by the authority, or by an approved sponsor
+    /// when its sponsor account is supplied
     pub fn create_identity(
         ctx: Context<CreateIdentity>,
+        identity_id: [u8; 32],
         did: String,
         metadata_uri: String,
         recovery_keys: Vec<Pubkey>,
+        recovery_threshold: u8,
     ) -> Result<()> {
         require!(did.len() <= 128, errors::IdentityError::DIDTooLong);
         require!(metadata_uri.len() <= 256, errors::IdentityError::URITooLong);
-        require!(recovery_keys.len() <= 5, errors::IdentityError::TooManyRecoveryKeys);
+        require!(
+            recovery_keys.len() <= IdentityAccount::MAX_RECOVERY_KEYS,
+            errors::IdentityError::TooManyRecoveryKeys
+        );
+        require!(
+            is_valid_recovery_threshold(recovery_threshold, recovery_keys.len()),
+            errors::IdentityError::InvalidRecoveryThreshold
+        );
+        require!(
+            recovery_keys.iter().enumerate().all(|(i, key)| !recovery_keys[..i].contains(key)),
+            errors::IdentityError::RecoveryKeyAlreadyExists
+        );
 
-        let identity = &mut ctx.accounts.identity_account;
         let clock = Clock::get()?;
+        let payer = ctx.accounts.payer.key();
+
+        let sponsor = match ctx.accounts.sponsor_account.as_mut() {
+            Some(sponsor_account) => {
+                let rent = ctx.accounts.identity_account.to_account_info().lamports()
+                    + ctx.accounts.authority_index.to_account_info().lamports()
+                    + ctx.accounts.did_index.to_account_info().lamports();
+                sponsor_account.charge(rent, clock.unix_timestamp)?;
+                Some(payer)
+            }
+            None => {
+                require_keys_eq!(
+                    payer,
+                    ctx.accounts.authority.key(),
+                    errors::IdentityError::UnauthorizedPayer
+                );
+                None
+            }
+        };
+
+        let identity = &mut ctx.accounts.identity_account;
 
         identity.authority = ctx.accounts.authority.key();
+        identity.identity_id = identity_id;
         identity.did = did;
         identity.verification_bitmap = 0;
         identity.reputation_score = 500; // Base score
@@ -51,9 +287,134 @@ pub mod identity_registry {
         identity.created_at = clock.unix_timestamp;
         identity.last_updated = clock.unix_timestamp;
         identity.metadata_uri = metadata_uri;
-        identity.recovery_keys = recovery_keys;
+        // Keys chosen at creation are usable immediately; later additions wait out a cooldown
+        identity.recovery_keys = recovery_keys
+            .into_iter()
+            .map(|key| RecoveryKey { key, active_at: clock.unix_timestamp })
+            .collect();
+        identity.recovery_threshold = recovery_threshold;
+        identity.pending_authority = None;
+        identity.verification_methods = Vec::new();
+        identity.services = Vec::new();
+        identity.metadata_hash = [0; 32];
+        identity.metadata_revision = 0;
+        identity.status = IdentityStatus::Active;
+        identity.deactivated_at = 0;
+        identity.sponsor = sponsor;
+        identity.identity_type = IdentityType::Individual;
+        identity.guardian = None;
+        identity.guardian_until = 0;
+        identity.freeze_reason = 0;
+        identity.freeze_case = [0; 32];
+        identity.frozen_at = 0;
+        identity.nominee = None;
+        identity.inactivity_threshold = 0;
+        identity.succession_started_at = 0;
+        identity.last_owner_activity = clock.unix_timestamp;
+        identity.strict_consent = false;
+        identity.bump = ctx.bumps.identity_account;
+
+        let authority_index = &mut ctx.accounts.authority_index;
+        authority_index.authority = identity.authority;
+        authority_index.identity = identity.key();
+        authority_index.bump = ctx.bumps.authority_index;
+
+        let did_index = &mut ctx.accounts.did_index;
+        did_index.did_hash = hash_did(&identity.did);
+        did_index.identity = identity.key();
+        did_index.authority = identity.authority;
+        did_index.tombstoned_at = 0;
+        did_index.bump = ctx.bumps.did_index;
+
+        Ok(())
+    }
+
+    /// Move a pre-existing identity from its authority-derived PDA to an identity_id-derived PDA
+    pub fn migrate_identity(
+        ctx: Context<MigrateIdentity>,
+        _legacy_seed: Pubkey,
+        identity_id: [u8; 32],
+        did: String,
+    ) -> Result<()> {
+        let legacy_info = ctx.accounts.legacy_identity.to_account_info();
+        let legacy = {
+            let data = legacy_info.try_borrow_data()?;
+            // Current identities share the seed prefix and discriminator, so only the
+            // legacy allocation size tells a pre-migration account apart
+            require!(
+                data.len() == 8 + LegacyIdentityAccount::LEN
+                    && &data[..8] == IdentityAccount::DISCRIMINATOR,
+                errors::IdentityError::AccountNotFound
+            );
+            LegacyIdentityAccount::deserialize(&mut &data[8..])?
+        };
+        require!(
+            legacy.authority == ctx.accounts.authority.key(),
+            errors::IdentityError::UnauthorizedMigration
+        );
+        require!(legacy.did == did, errors::IdentityError::DIDMismatch);
+
+        let identity = &mut ctx.accounts.identity_account;
+        identity.authority = legacy.authority;
+        identity.identity_id = identity_id;
+        identity.did = legacy.did;
+        identity.verification_bitmap = legacy.verification_bitmap;
+        identity.reputation_score = legacy.reputation_score;
+        identity.staked_amount = legacy.staked_amount;
+        identity.created_at = legacy.created_at;
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.metadata_uri = legacy.metadata_uri;
+        // Legacy identities allowed any single recovery key, so start at 1-of-N
+        identity.recovery_threshold = if legacy.recovery_keys.is_empty() { 0 } else { 1 };
+        identity.recovery_keys = legacy.recovery_keys
+            .into_iter()
+            .map(|key| RecoveryKey { key, active_at: identity.last_updated })
+            .collect();
+        identity.pending_authority = None;
+        identity.verification_methods = Vec::new();
+        identity.services = Vec::new();
+        identity.metadata_hash = [0; 32];
+        identity.metadata_revision = 0;
+        identity.status = IdentityStatus::Active;
+        identity.deactivated_at = 0;
+        identity.sponsor = None;
+        identity.identity_type = IdentityType::Individual;
+        identity.guardian = None;
+        identity.guardian_until = 0;
+        identity.freeze_reason = 0;
+        identity.freeze_case = [0; 32];
+        identity.frozen_at = 0;
+        identity.nominee = None;
+        identity.inactivity_threshold = 0;
+        identity.succession_started_at = 0;
+        identity.last_owner_activity = identity.last_updated;
+        identity.strict_consent = false;
         identity.bump = ctx.bumps.identity_account;
 
+        let authority_index = &mut ctx.accounts.authority_index;
+        authority_index.authority = identity.authority;
+        authority_index.identity = identity.key();
+        authority_index.bump = ctx.bumps.authority_index;
+
+        let did_index = &mut ctx.accounts.did_index;
+        did_index.did_hash = hash_did(&identity.did);
+        did_index.identity = identity.key();
+        did_index.authority = identity.authority;
+        did_index.tombstoned_at = 0;
+        did_index.bump = ctx.bumps.did_index;
+
+        // Close the legacy account and return its rent to the authority
+        let authority_info = ctx.accounts.authority.to_account_info();
+        let legacy_lamports = legacy_info.lamports();
+        **legacy_info.try_borrow_mut_lamports()? = 0;
+        **authority_info.try_borrow_mut_lamports()? = authority_info.lamports()
+            .checked_add(legacy_lamports)
+            .ok_or(errors::IdentityError::Overflow)?;
+        legacy_info.assign(&system_program::ID);
+        legacy_info.resize(0)?;
+
+        msg!("Identity migrated from {} to {}", legacy_info.key(), identity.key());
+
         Ok(())
     }
 
@@ -61,6 +422,8 @@ pub mod identity_registry {
         ctx: Context<UpdateVerificationStatus>,
         verification_type: u8,
         verified: bool,
+        assurance_level: u8,
+        nullifier: [u8; 32],
     ) -> Result<()> {
         // Verify caller is the authorized oracle
         require!(
@@ -70,12 +433,64 @@ pub mod identity_registry {
         require!(verification_type < 64, errors::IdentityError::InvalidVerificationType);
 
         let identity = &mut ctx.accounts.identity_account;
+        identity.require_not_deactivated()?;
         let clock = Clock::get()?;
 
+        // The request must be an oracle-program account whose identity (after the
+        // discriminator) is this one, so a result can't be credited to another identity
+        {
+            let source = &ctx.accounts.source_request;
+            let data = source.try_borrow_data()?;
+            require!(
+                source.owner == ctx.accounts.oracle.owner
+                    && data.len() >= 8 + 32
+                    && data[8..40] == identity.key().to_bytes(),
+                errors::IdentityError::SourceRequestMismatch
+            );
+        }
+
+        let record = &mut ctx.accounts.verification_record;
+        record.identity = identity.key();
+        record.verification_type = verification_type;
+        record.source_request = ctx.accounts.source_request.key();
+        record.bump = ctx.bumps.verification_record;
+
+        // Setting the Aadhaar bit claims the nullifier, or fails if another identity holds it
+        if verified && verification_type == AadhaarNullifier::VERIFICATION_TYPE {
+            require!(nullifier != [0; 32], errors::IdentityError::MissingNullifier);
+            let claim = ctx.accounts.aadhaar_nullifier
+                .as_mut()
+                .ok_or(errors::IdentityError::MissingNullifier)?;
+
+            if claim.identity == Pubkey::default() {
+                claim.nullifier = nullifier;
+                claim.identity = identity.key();
+                claim.claimed_at = clock.unix_timestamp;
+                claim.bump = ctx.bumps.aadhaar_nullifier.ok_or(errors::IdentityError::MissingNullifier)?;
+            } else {
+                require_keys_eq!(
+                    claim.identity,
+                    identity.key(),
+                    errors::IdentityError::NullifierAlreadyClaimed
+                );
+            }
+        }
+
         if verified {
+            let validity = ctx.accounts.config.verification_validity_for(verification_type);
             identity.verification_bitmap |= 1 << verification_type;
+            record.verified_at = clock.unix_timestamp;
+            record.expires_at = if validity > 0 {
+                clock.unix_timestamp.checked_add(validity).ok_or(errors::IdentityError::Overflow)?
+            } else {
+                0 // Never expires
+            };
+            record.assurance_level = assurance_level;
         } else {
             identity.verification_bitmap &= !(1 << verification_type);
+            record.verified_at = 0;
+            record.expires_at = 0;
+            record.assurance_level = 0;
         }
 
         identity.last_updated = clock.unix_timestamp;
@@ -85,6 +500,49 @@ pub mod identity_registry {
         Ok(())
     }
 
+    /// Clear a verification bit whose record has expired (crank operation)
+    pub fn clear_expired_verification(ctx: Context<ClearExpiredVerification>) -> Result<()> {
+        let record = &ctx.accounts.verification_record;
+        let identity = &mut ctx.accounts.identity_account;
+        let clock = Clock::get()?;
+
+        require!(
+            record.expires_at > 0 && clock.unix_timestamp >= record.expires_at,
+            errors::IdentityError::VerificationNotExpired
+        );
+
+        identity.verification_bitmap &= !(1 << record.verification_type);
+        identity.last_updated = clock.unix_timestamp;
+
+        msg!("Expired verification cleared: type={}", record.verification_type);
+
+        Ok(())
+    }
+
+    /// Release an Aadhaar nullifier held by a tombstoned or purged identity (admin only)
+    pub fn release_nullifier(ctx: Context<ReleaseNullifier>) -> Result<()> {
+        let identity_info = &ctx.accounts.identity_account;
+
+        // A purged identity no longer exists; a live one must have been deactivated
+        if !identity_info.data_is_empty() && identity_info.owner == &crate::ID {
+            let data = identity_info.try_borrow_data()?;
+            let identity = IdentityAccount::try_deserialize(&mut &data[..])?;
+            require!(
+                identity.status == IdentityStatus::Deactivated,
+                errors::IdentityError::NullifierIdentityActive
+            );
+        }
+
+        let claim = &ctx.accounts.aadhaar_nullifier;
+        emit!(NullifierReleased {
+            nullifier: claim.nullifier,
+            identity: claim.identity,
+            released_by: ctx.accounts.admin.key(),
+        });
+
+        Ok(())
+    }
+
     pub fn update_reputation(
         ctx: Context<UpdateReputation>,
         new_score: u64,
@@ -96,6 +554,7 @@ pub mod identity_registry {
         );
 
         let identity = &mut ctx.accounts.identity_account;
+        identity.require_not_deactivated()?;
         let clock = Clock::get()?;
 
         identity.reputation_score = new_score;
@@ -118,6 +577,7 @@ pub mod identity_registry {
         );
 
         let identity = &mut ctx.accounts.identity_account;
+        identity.require_not_deactivated()?;
         let clock = Clock::get()?;
 
         identity.staked_amount = new_amount;
@@ -133,135 +593,2897 @@ pub mod identity_registry {
         recovery_key: Pubkey,
     ) -> Result<()> {
         let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        let clock = Clock::get()?;
         require!(
-            identity.recovery_keys.len() < 5,
+            identity.recovery_keys.len() < IdentityAccount::MAX_RECOVERY_KEYS,
             errors::IdentityError::TooManyRecoveryKeys
         );
+        require!(
+            identity.find_recovery_key(&recovery_key).is_none(),
+            errors::IdentityError::RecoveryKeyAlreadyExists
+        );
 
-        identity.recovery_keys.push(recovery_key);
-        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.recovery_keys.push(RecoveryKey {
+            key: recovery_key,
+            active_at: clock.unix_timestamp
+                .checked_add(IdentityAccount::RECOVERY_KEY_COOLDOWN)
+                .ok_or(errors::IdentityError::Overflow)?,
+        });
+        // The first recovery key enables recovery with a 1-of-1 threshold
+        if identity.recovery_threshold == 0 {
+            identity.recovery_threshold = 1;
+        }
+        identity.last_updated = clock.unix_timestamp;
+        identity.last_owner_activity = clock.unix_timestamp;
 
         Ok(())
     }
 
-    pub fn recover_identity(
-        ctx: Context<RecoverIdentity>,
-        new_authority: Pubkey,
+    /// Remove a recovery key (authority only)
+    pub fn remove_recovery_key(
+        ctx: Context<ManageRecoveryKeys>,
+        recovery_key: Pubkey,
     ) -> Result<()> {
         let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        let index = identity
+            .find_recovery_key(&recovery_key)
+            .ok_or(errors::IdentityError::RecoveryKeyNotFound)?;
 
+        identity.recovery_keys.remove(index);
+        if identity.recovery_keys.is_empty() {
+            identity.recovery_threshold = 0;
+        }
         require!(
-            identity.recovery_keys.contains(&ctx.accounts.recovery_signer.key()),
-            errors::IdentityError::UnauthorizedRecovery
+            is_valid_recovery_threshold(identity.recovery_threshold, identity.recovery_keys.len()),
+            errors::IdentityError::InvalidRecoveryThreshold
+        );
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
+
+        msg!("Recovery key {} removed", recovery_key);
+
+        Ok(())
+    }
+
+    /// Swap one recovery key for another; the new key is subject to the usual cooldown
+    pub fn replace_recovery_key(
+        ctx: Context<ManageRecoveryKeys>,
+        old_recovery_key: Pubkey,
+        new_recovery_key: Pubkey,
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        let clock = Clock::get()?;
+        let index = identity
+            .find_recovery_key(&old_recovery_key)
+            .ok_or(errors::IdentityError::RecoveryKeyNotFound)?;
+        require!(
+            identity.find_recovery_key(&new_recovery_key).is_none(),
+            errors::IdentityError::RecoveryKeyAlreadyExists
         );
 
-        identity.authority = new_authority;
+        identity.recovery_keys[index] = RecoveryKey {
+            key: new_recovery_key,
+            active_at: clock.unix_timestamp
+                .checked_add(IdentityAccount::RECOVERY_KEY_COOLDOWN)
+                .ok_or(errors::IdentityError::Overflow)?,
+        };
+        identity.last_updated = clock.unix_timestamp;
+        identity.last_owner_activity = clock.unix_timestamp;
+
+        msg!("Recovery key {} replaced by {}", old_recovery_key, new_recovery_key);
+
+        Ok(())
+    }
+
+    /// Propose handing the identity to a new authority, which must accept (authority only)
+    pub fn transfer_authority(
+        ctx: Context<TransferAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        identity.require_no_guardian()?;
+
+        identity.pending_authority = Some(new_authority);
         identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
+
+        msg!("Authority transfer proposed from {} to {}", identity.authority, new_authority);
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct InitializeConfig<'info> {
-    #[account(
-        init,
-        payer = admin,
-        space = 8 + GlobalConfig::LEN,
-        seeds = [b"config"],
-        bump
-    )]
-    pub config: Account<'info, GlobalConfig>,
+    /// Withdraw a pending authority transfer (authority only)
+    pub fn cancel_authority_transfer(ctx: Context<TransferAuthority>) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_not_deactivated()?;
+        require!(
+            identity.pending_authority.is_some(),
+            errors::IdentityError::NoPendingAuthorityTransfer
+        );
 
-    #[account(mut)]
-    pub admin: Signer<'info>,
+        identity.pending_authority = None;
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
 
-    pub system_program: Program<'info, System>,
-}
+        msg!("Authority transfer cancelled");
 
-#[derive(Accounts)]
-pub struct CreateIdentity<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + IdentityAccount::LEN,
-        seeds = [b"identity", authority.key().as_ref()],
-        bump
-    )]
-    pub identity_account: Account<'info, IdentityAccount>,
+        Ok(())
+    }
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    /// Complete a pending authority transfer, signed by the new authority
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        identity.require_no_guardian()?;
+        let old_authority = identity.authority;
 
-    pub system_program: Program<'info, System>,
-}
+        identity.authority = ctx.accounts.new_authority.key();
+        identity.pending_authority = None;
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
 
-#[derive(Accounts)]
-pub struct UpdateVerificationStatus<'info> {
-    #[account(
-        mut,
-        seeds = [b"identity", identity_account.authority.as_ref()],
-        bump = identity_account.bump
-    )]
-    pub identity_account: Account<'info, IdentityAccount>,
+        let authority_index = &mut ctx.accounts.new_authority_index;
+        authority_index.authority = identity.authority;
+        authority_index.identity = identity.key();
+        authority_index.bump = ctx.bumps.new_authority_index;
 
-    pub oracle: Signer<'info>,
+        ctx.accounts.did_index.authority = identity.authority;
 
-    #[account(seeds = [b"config"], bump)]
-    pub config: Account<'info, GlobalConfig>,
-}
+        msg!("Authority of {} transferred from {} to {}",
+            identity.key(), old_authority, identity.authority);
 
-#[derive(Accounts)]
-pub struct UpdateReputation<'info> {
-    #[account(
-        mut,
-        seeds = [b"identity", identity_account.authority.as_ref()],
-        bump = identity_account.bump
-    )]
-    pub identity_account: Account<'info, IdentityAccount>,
+        Ok(())
+    }
 
-    pub reputation_engine: Signer<'info>,
+    /// Set how many recovery keys must approve a recovery (authority only)
+    pub fn set_recovery_threshold(
+        ctx: Context<SetRecoveryThreshold>,
+        recovery_threshold: u8,
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        require!(
+            is_valid_recovery_threshold(recovery_threshold, identity.recovery_keys.len()),
+            errors::IdentityError::InvalidRecoveryThreshold
+        );
 
-    #[account(seeds = [b"config"], bump)]
-    pub config: Account<'info, GlobalConfig>,
-}
+        identity.recovery_threshold = recovery_threshold;
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
 
-#[derive(Accounts)]
-pub struct AddRecoveryKey<'info> {
-    #[account(
-        mut,
-        seeds = [b"identity", authority.key().as_ref()],
-        bump = identity_account.bump,
-        has_one = authority
-    )]
-    pub identity_account: Account<'info, IdentityAccount>,
+        msg!("Recovery threshold set to {} of {}",
+            recovery_threshold, identity.recovery_keys.len());
 
-    pub authority: Signer<'info>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct RecoverIdentity<'info> {
-    #[account(
-        mut,
-        seeds = [b"identity", identity_account.authority.as_ref()],
-        bump = identity_account.bump
-    )]
-    pub identity_account: Account<'info, IdentityAccount>,
+    /// Open a recovery proposal; the proposing recovery key counts as the first approval
+    pub fn propose_recovery(
+        ctx: Context<ProposeRecovery>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let identity = &ctx.accounts.identity_account;
+        identity.require_active()?;
+        identity.require_no_guardian()?;
+        let proposal = &mut ctx.accounts.recovery_proposal;
+        let recovery_signer = ctx.accounts.recovery_signer.key();
+        let clock = Clock::get()?;
 
-    pub recovery_signer: Signer<'info>,
-}
+        require!(
+            identity.find_recovery_key(&recovery_signer).is_some(),
+            errors::IdentityError::UnauthorizedRecovery
+        );
+        require!(
+            identity.is_active_recovery_key(&recovery_signer, clock.unix_timestamp),
+            errors::IdentityError::RecoveryKeyNotActive
+        );
 
-#[derive(Accounts)]
-pub struct UpdateStakedAmount<'info> {
-    #[account(
-        mut,
-        seeds = [b"identity", identity_account.authority.as_ref()],
-        bump = identity_account.bump
-    )]
-    pub identity_account: Account<'info, IdentityAccount>,
+        proposal.identity = identity.key();
+        proposal.proposer = recovery_signer;
+        proposal.new_authority = new_authority;
+        proposal.approvals = vec![recovery_signer];
+        proposal.created_at = clock.unix_timestamp;
+        proposal.executable_at = clock.unix_timestamp
+            .checked_add(RecoveryProposal::RECOVERY_DELAY)
+            .ok_or(errors::IdentityError::Overflow)?;
+        proposal.bump = ctx.bumps.recovery_proposal;
 
-    pub staking_manager: Signer<'info>,
+        msg!("Recovery proposed for {} by {}, executable at {}",
+            identity.key(), recovery_signer, proposal.executable_at);
 
-    #[account(seeds = [b"config"], bump)]
+        Ok(())
+    }
+
+    /// Approve a pending recovery proposal with another recovery key
+    pub fn approve_recovery(ctx: Context<ApproveRecovery>) -> Result<()> {
+        let identity = &ctx.accounts.identity_account;
+        identity.require_active()?;
+        let proposal = &mut ctx.accounts.recovery_proposal;
+        let recovery_signer = ctx.accounts.recovery_signer.key();
+        let clock = Clock::get()?;
+
+        require!(
+            identity.find_recovery_key(&recovery_signer).is_some(),
+            errors::IdentityError::UnauthorizedRecovery
+        );
+        require!(
+            identity.is_active_recovery_key(&recovery_signer, clock.unix_timestamp),
+            errors::IdentityError::RecoveryKeyNotActive
+        );
+        require!(
+            !proposal.approvals.contains(&recovery_signer),
+            errors::IdentityError::RecoveryAlreadyApproved
+        );
+
+        proposal.approvals.push(recovery_signer);
+
+        msg!("Recovery approved by {} ({} approvals)", recovery_signer, proposal.approvals.len());
+
+        Ok(())
+    }
+
+    /// Execute a recovery proposal once it has enough approvals and the delay has elapsed
+    pub fn recover_identity(ctx: Context<RecoverIdentity>) -> Result<()> {
+        let proposal = &ctx.accounts.recovery_proposal;
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        identity.require_no_guardian()?;
+        let clock = Clock::get()?;
+
+        require!(
+            clock.unix_timestamp >= proposal.executable_at,
+            errors::IdentityError::RecoveryDelayNotElapsed
+        );
+
+        // Only count approvals from keys that are still registered and past their cooldown
+        let approvals = proposal.approvals
+            .iter()
+            .filter(|key| identity.is_active_recovery_key(key, clock.unix_timestamp))
+            .count();
+        require!(
+            identity.recovery_threshold > 0 && approvals >= identity.recovery_threshold as usize,
+            errors::IdentityError::InsufficientRecoveryApprovals
+        );
+
+        identity.authority = proposal.new_authority;
+        identity.pending_authority = None;
+        identity.last_updated = clock.unix_timestamp;
+        identity.last_owner_activity = clock.unix_timestamp;
+
+        let authority_index = &mut ctx.accounts.new_authority_index;
+        authority_index.authority = identity.authority;
+        authority_index.identity = identity.key();
+        authority_index.bump = ctx.bumps.new_authority_index;
+
+        ctx.accounts.did_index.authority = identity.authority;
+
+        msg!("Identity {} recovered to {}", identity.key(), proposal.new_authority);
+
+        Ok(())
+    }
+
+    /// Veto a pending recovery proposal (authority only)
+    pub fn cancel_recovery(ctx: Context<CancelRecovery>) -> Result<()> {
+        ctx.accounts.identity_account.last_owner_activity = Clock::get()?.unix_timestamp;
+        msg!("Recovery proposal by {} cancelled", ctx.accounts.recovery_proposal.proposer);
+        Ok(())
+    }
+
+    /// Resolve a DID to its identity account (view helper, result returned as return data)
+    pub fn resolve_did(ctx: Context<ResolveDid>, did: String) -> Result<Pubkey> {
+        let did_index = &ctx.accounts.did_index;
+
+        msg!("DID {} resolves to identity {} (authority {})",
+            did, did_index.identity, did_index.authority);
+
+        Ok(did_index.identity)
+    }
+
+    /// Check an identity against verification, reputation and stake requirements (CPI gate).
+    /// Fails with a specific IdentityError when unmet, otherwise returns an IdentityAssertion.
+    /// The VerificationRecord of every required verification type must follow as remaining
+    /// accounts in ascending type order, so a lapsed verification fails even before
+    /// clear_expired_verification has cleared its bit.
+    pub fn assert_identity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AssertIdentity<'info>>,
+        required_verifications: u64,
+        min_reputation: u64,
+        min_stake: u64,
+    ) -> Result<IdentityAssertion> {
+        let identity = &ctx.accounts.identity_account;
+        identity.require_active()?;
+
+        // In strict mode the calling relying party must hold consent covering the checked bits
+        if identity.strict_consent {
+            let now = Clock::get()?.unix_timestamp;
+            let relying_party = ctx.accounts.relying_party
+                .as_ref()
+                .ok_or(errors::IdentityError::ConsentRequired)?;
+            require!(
+                ctx.accounts.consent.as_ref().is_some_and(|consent| consent.allows(
+                    &identity.key(),
+                    &relying_party.key(),
+                    required_verifications,
+                    now,
+                )),
+                errors::IdentityError::ConsentRequired
+            );
+        }
+
+        // A linked wallet only carries the identity's verifications if it inherits them
+        let (wallet, verification_bitmap) = match &ctx.accounts.linked_wallet {
+            Some(linked) if linked.inherits_verification => {
+                (linked.wallet, identity.verification_bitmap)
+            }
+            Some(linked) => (linked.wallet, 0),
+            None => (identity.authority, identity.verification_bitmap),
+        };
+
+        require!(
+            verification_bitmap & required_verifications == required_verifications,
+            errors::IdentityError::MissingRequiredVerification
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let required_types = (0..64u8).filter(|t| required_verifications & (1 << t) != 0);
+        require!(
+            ctx.remaining_accounts.len() == required_types.clone().count(),
+            errors::IdentityError::MissingVerificationRecord
+        );
+        for (verification_type, info) in required_types.zip(ctx.remaining_accounts) {
+            let record: Account<VerificationRecord> = Account::try_from(info)?;
+            require!(
+                record.identity == identity.key() && record.verification_type == verification_type,
+                errors::IdentityError::MissingVerificationRecord
+            );
+            require!(
+                record.expires_at == 0 || now < record.expires_at,
+                errors::IdentityError::VerificationExpired
+            );
+        }
+
+        require!(
+            identity.reputation_score >= min_reputation,
+            errors::IdentityError::InsufficientReputation
+        );
+        require!(
+            identity.staked_amount >= min_stake,
+            errors::IdentityError::InsufficientStake
+        );
+
+        Ok(IdentityAssertion {
+            identity: identity.key(),
+            authority: identity.authority,
+            wallet,
+            verification_bitmap,
+            reputation_score: identity.reputation_score,
+            staked_amount: identity.staked_amount,
+            status: identity.status,
+        })
+    }
+
+    /// Answer the same questions as assert_identity for a relying-party pseudonym, from
+    /// its current attestation, without touching the root identity (CPI gate).
+    /// `max_age` bounds how old the attestation may be and must be positive.
+    pub fn assert_pseudonym(
+        ctx: Context<AssertPseudonym>,
+        required_verifications: u64,
+        min_reputation: u64,
+        max_age: i64,
+    ) -> Result<PseudonymAssertion> {
+        let pseudonym = &ctx.accounts.pseudonym;
+        let clock = Clock::get()?;
+
+        match pseudonym.status {
+            IdentityStatus::Active => {}
+            IdentityStatus::Frozen => return err!(errors::IdentityError::IdentityFrozen),
+            IdentityStatus::Deactivated => return err!(errors::IdentityError::IdentityDeactivated),
+        }
+        require!(max_age > 0, errors::IdentityError::InvalidConfigValue);
+        require!(
+            clock.unix_timestamp < pseudonym.expires_at,
+            errors::IdentityError::PseudonymAttestationExpired
+        );
+        require!(
+            clock.unix_timestamp - pseudonym.attested_at <= max_age,
+            errors::IdentityError::PseudonymStale
+        );
+        require!(
+            pseudonym.verification_bitmap & required_verifications == required_verifications,
+            errors::IdentityError::MissingRequiredVerification
+        );
+        require!(
+            pseudonym.reputation_score >= min_reputation,
+            errors::IdentityError::InsufficientReputation
+        );
+
+        Ok(PseudonymAssertion {
+            pseudonym: pseudonym.key(),
+            relying_party: pseudonym.relying_party,
+            verification_bitmap: pseudonym.verification_bitmap,
+            reputation_score: pseudonym.reputation_score,
+            attested_at: pseudonym.attested_at,
+            expires_at: pseudonym.expires_at,
+        })
+    }
+
+    /// Create an unattested pseudonym for a relying party. Signed by a fresh pseudonym
+    /// owner key, never by the root identity; the salt behind `commitment` stays off-chain.
+    pub fn register_pseudonym(
+        ctx: Context<RegisterPseudonym>,
+        relying_party: Pubkey,
+        commitment: [u8; 32],
+    ) -> Result<()> {
+        let pseudonym = &mut ctx.accounts.pseudonym;
+        pseudonym.relying_party = relying_party;
+        pseudonym.commitment = commitment;
+        pseudonym.owner = ctx.accounts.owner.key();
+        pseudonym.verification_bitmap = 0;
+        pseudonym.reputation_score = 0;
+        pseudonym.status = IdentityStatus::Active;
+        pseudonym.attested_at = 0;
+        pseudonym.expires_at = 0; // Unusable until attested
+        pseudonym.bump = ctx.bumps.pseudonym;
+
+        Ok(())
+    }
+
+    /// Record the root identity's current status and attributes on a pseudonym (pseudonym
+    /// attester only). The owner opens the commitment to the attester off-chain; the attester
+    /// re-attests with a non-Active status when the root is frozen or deactivated, and caps
+    /// `expires_at` at the earliest expiry of the attested verifications.
+    pub fn attest_pseudonym(
+        ctx: Context<AttestPseudonym>,
+        status: IdentityStatus,
+        verification_bitmap: u64,
+        reputation_score: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let max_expires_at = clock.unix_timestamp
+            .checked_add(Pseudonym::MAX_ATTESTATION_PERIOD)
+            .ok_or(errors::IdentityError::Overflow)?;
+        require!(
+            expires_at > clock.unix_timestamp && expires_at <= max_expires_at,
+            errors::IdentityError::InvalidAttestationExpiry
+        );
+
+        let pseudonym = &mut ctx.accounts.pseudonym;
+        pseudonym.status = status;
+        pseudonym.verification_bitmap = verification_bitmap;
+        pseudonym.reputation_score = reputation_score;
+        pseudonym.attested_at = clock.unix_timestamp;
+        pseudonym.expires_at = expires_at;
+
+        Ok(())
+    }
+
+    /// Close a pseudonym and reclaim its rent (pseudonym owner only)
+    pub fn revoke_pseudonym(ctx: Context<RevokePseudonym>) -> Result<()> {
+        msg!("Pseudonym {} revoked", ctx.accounts.pseudonym.key());
+        Ok(())
+    }
+
+    /// Require relying parties to present a consent grant when gating on this identity
+    /// (authority only)
+    pub fn set_strict_consent(ctx: Context<SetStrictConsent>, strict: bool) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        identity.strict_consent = strict;
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
+
+        msg!("Strict consent for {} set to {}", identity.key(), strict);
+
+        Ok(())
+    }
+
+    /// Grant or update a relying party's consent to rely on verification facts (authority only)
+    pub fn grant_consent(
+        ctx: Context<GrantConsent>,
+        relying_party: Pubkey,
+        verification_mask: u64,
+        purpose: u16,
+        expires_at: i64,
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        let clock = Clock::get()?;
+        require!(expires_at > clock.unix_timestamp, errors::IdentityError::InvalidConsentExpiry);
+        identity.last_owner_activity = clock.unix_timestamp;
+
+        let consent = &mut ctx.accounts.consent;
+        consent.identity = identity.key();
+        consent.relying_party = relying_party;
+        consent.verification_mask = verification_mask;
+        consent.purpose = purpose;
+        consent.granted_at = clock.unix_timestamp;
+        consent.expires_at = expires_at;
+        consent.bump = ctx.bumps.consent;
+
+        emit!(ConsentGranted {
+            identity: identity.key(),
+            relying_party,
+            verification_mask,
+            purpose,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a relying party's consent and reclaim its rent (authority only)
+    pub fn revoke_consent(ctx: Context<RevokeConsent>) -> Result<()> {
+        ctx.accounts.identity_account.last_owner_activity = Clock::get()?.unix_timestamp;
+        emit!(ConsentRevoked {
+            identity: ctx.accounts.consent.identity,
+            relying_party: ctx.accounts.consent.relying_party,
+        });
+
+        Ok(())
+    }
+
+    /// Point the identity at new metadata, pinned by its content hash (authority only)
+    pub fn update_metadata(
+        ctx: Context<UpdateMetadata>,
+        metadata_uri: String,
+        metadata_hash: [u8; 32],
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        let clock = Clock::get()?;
+        identity.require_active()?;
+        require!(
+            identity.is_authorized(
+                &identity.key(),
+                &ctx.accounts.signer.key(),
+                ctx.accounts.delegate.as_deref(),
+                delegate_permissions::UPDATE_METADATA,
+                clock.unix_timestamp,
+            ),
+            errors::IdentityError::UnauthorizedSigner
+        );
+        require!(metadata_uri.len() <= 256, errors::IdentityError::URITooLong);
+
+        identity.metadata_uri = metadata_uri;
+        identity.metadata_hash = metadata_hash;
+        identity.metadata_revision = identity.metadata_revision
+            .checked_add(1)
+            .ok_or(errors::IdentityError::Overflow)?;
+        identity.last_updated = clock.unix_timestamp;
+        // Delegates act for the owner but do not prove the owner is still around
+        if ctx.accounts.signer.key() == identity.controller() {
+            identity.last_owner_activity = clock.unix_timestamp;
+        }
+
+        msg!("Metadata updated to revision {}", identity.metadata_revision);
+
+        Ok(())
+    }
+
+    /// Register a delegate key with scoped permissions and an expiry (authority only)
+    pub fn add_delegate(
+        ctx: Context<AddDelegate>,
+        delegate: Pubkey,
+        permissions: u8,
+        expires_at: i64,
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        let clock = Clock::get()?;
+        identity.last_owner_activity = clock.unix_timestamp;
+
+        require!(
+            permissions != 0 && permissions & !delegate_permissions::ALL == 0,
+            errors::IdentityError::InvalidDelegatePermissions
+        );
+        require!(expires_at > clock.unix_timestamp, errors::IdentityError::InvalidDelegateExpiry);
+
+        let delegate_account = &mut ctx.accounts.delegate_account;
+        delegate_account.identity = identity.key();
+        delegate_account.delegate = delegate;
+        delegate_account.permissions = permissions;
+        delegate_account.expires_at = expires_at;
+        delegate_account.created_at = clock.unix_timestamp;
+        delegate_account.granted_by = ctx.accounts.authority.key();
+        delegate_account.bump = ctx.bumps.delegate_account;
+
+        msg!("Delegate {} added with permissions {:#05b} until {}", delegate, permissions, expires_at);
+
+        Ok(())
+    }
+
+    /// Revoke a delegate key and reclaim its rent (authority only)
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+        ctx.accounts.identity_account.last_owner_activity = Clock::get()?.unix_timestamp;
+        msg!("Delegate {} revoked", ctx.accounts.delegate_account.delegate);
+        Ok(())
+    }
+
+    /// Link a secondary wallet to the identity; both the authority and the wallet sign
+    pub fn link_wallet(ctx: Context<LinkWallet>, inherits_verification: bool) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        let clock = Clock::get()?;
+        require!(
+            ctx.accounts.wallet.key() != identity.authority,
+            errors::IdentityError::WalletIsAuthority
+        );
+
+        let linked = &mut ctx.accounts.linked_wallet;
+        linked.identity = identity.key();
+        linked.wallet = ctx.accounts.wallet.key();
+        linked.inherits_verification = inherits_verification;
+        linked.linked_at = clock.unix_timestamp;
+        linked.payer = ctx.accounts.authority.key();
+        linked.bump = ctx.bumps.linked_wallet;
+        identity.last_owner_activity = clock.unix_timestamp;
+
+        emit!(WalletLinked {
+            identity: linked.identity,
+            wallet: linked.wallet,
+            inherits_verification,
+        });
+
+        Ok(())
+    }
+
+    /// Choose whether a linked wallet inherits verification status (authority only)
+    pub fn set_wallet_inheritance(
+        ctx: Context<SetWalletInheritance>,
+        inherits_verification: bool,
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        identity.last_owner_activity = Clock::get()?.unix_timestamp;
+        ctx.accounts.linked_wallet.inherits_verification = inherits_verification;
+
+        Ok(())
+    }
+
+    /// Unlink a wallet, signed by either the wallet itself or the identity's controller
+    /// (the guardian while one is set, else the authority)
+    pub fn unlink_wallet(ctx: Context<UnlinkWallet>) -> Result<()> {
+        let linked = &ctx.accounts.linked_wallet;
+        let signer = ctx.accounts.signer.key();
+
+        // The identity may already be purged, so the wallet can always unlink itself
+        let by_controller = ctx.accounts.identity_account
+            .as_ref()
+            .is_some_and(|identity| identity.controller() == signer);
+        require!(
+            signer == linked.wallet || by_controller,
+            errors::IdentityError::UnauthorizedUnlink
+        );
+        if by_controller {
+            if let Some(identity) = ctx.accounts.identity_account.as_mut() {
+                identity.last_owner_activity = Clock::get()?.unix_timestamp;
+            }
+        }
+
+        emit!(WalletUnlinked {
+            identity: linked.identity,
+            wallet: linked.wallet,
+            unlinked_by: signer,
+        });
+
+        Ok(())
+    }
+
+    /// Place the identity under a guardian until `guardian_until`; signed by both the
+    /// authority (the dependent) and the guardian
+    pub fn assign_guardian(ctx: Context<AssignGuardian>, guardian_until: i64) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        let clock = Clock::get()?;
+
+        require!(
+            identity.identity_type == IdentityType::Individual,
+            errors::IdentityError::InvalidIdentityType
+        );
+        require!(
+            ctx.accounts.guardian.key() != identity.authority,
+            errors::IdentityError::InvalidGuardian
+        );
+        require!(guardian_until > clock.unix_timestamp, errors::IdentityError::InvalidHandoverTime);
+
+        identity.identity_type = IdentityType::Dependent;
+        identity.guardian = Some(ctx.accounts.guardian.key());
+        identity.guardian_until = guardian_until;
+        identity.pending_authority = None;
+        identity.last_updated = clock.unix_timestamp;
+        identity.last_owner_activity = clock.unix_timestamp;
+
+        emit!(GuardianAssigned {
+            identity: identity.key(),
+            guardian: ctx.accounts.guardian.key(),
+            guardian_until,
+        });
+
+        Ok(())
+    }
+
+    /// Take full control of a dependent identity once the handover time has passed
+    /// (authority only)
+    pub fn claim_control(ctx: Context<ClaimControl>) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        let clock = Clock::get()?;
+
+        let guardian = identity.guardian.ok_or(errors::IdentityError::NoGuardian)?;
+        require!(
+            clock.unix_timestamp >= identity.guardian_until,
+            errors::IdentityError::HandoverNotReached
+        );
+
+        identity.identity_type = IdentityType::Individual;
+        identity.guardian = None;
+        identity.guardian_until = 0;
+        identity.last_updated = clock.unix_timestamp;
+        identity.last_owner_activity = clock.unix_timestamp;
+
+        emit!(GuardianReleased {
+            identity: identity.key(),
+            guardian,
+        });
+
+        Ok(())
+    }
+
+    /// Turn an individual identity into an organization (authority only)
+    pub fn register_organization(ctx: Context<RegisterOrganization>) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        require!(
+            identity.identity_type == IdentityType::Individual,
+            errors::IdentityError::InvalidIdentityType
+        );
+
+        identity.identity_type = IdentityType::Organization;
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
+
+        emit!(OrganizationRegistered {
+            identity: identity.key(),
+            authority: identity.authority,
+        });
+
+        Ok(())
+    }
+
+    /// Add a member to an organization's roster (org controller or ADMIN member)
+    pub fn add_org_member(ctx: Context<AddOrgMember>, member: Pubkey, roles: u8) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        require!(
+            identity.has_org_role(
+                &identity.key(),
+                &ctx.accounts.admin.key(),
+                ctx.accounts.admin_membership.as_deref(),
+                org_roles::ADMIN,
+            ),
+            errors::IdentityError::MissingOrgRole
+        );
+        if ctx.accounts.admin.key() == identity.controller() {
+            identity.last_owner_activity = Clock::get()?.unix_timestamp;
+        }
+        require!(
+            roles != 0 && roles & !org_roles::ALL == 0,
+            errors::IdentityError::InvalidOrgRoles
+        );
+
+        let org_member = &mut ctx.accounts.org_member;
+        org_member.organization = identity.key();
+        org_member.member = member;
+        org_member.roles = roles;
+        org_member.added_at = Clock::get()?.unix_timestamp;
+        org_member.bump = ctx.bumps.org_member;
+
+        emit!(OrgMemberUpdated {
+            organization: identity.key(),
+            member,
+            roles,
+            updated_by: ctx.accounts.admin.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Change the roles of an organization member (org controller or ADMIN member)
+    pub fn update_org_member(ctx: Context<ManageOrgMember>, roles: u8) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        require!(
+            identity.has_org_role(
+                &identity.key(),
+                &ctx.accounts.admin.key(),
+                ctx.accounts.admin_membership.as_deref(),
+                org_roles::ADMIN,
+            ),
+            errors::IdentityError::MissingOrgRole
+        );
+        if ctx.accounts.admin.key() == identity.controller() {
+            identity.last_owner_activity = Clock::get()?.unix_timestamp;
+        }
+        require!(
+            roles != 0 && roles & !org_roles::ALL == 0,
+            errors::IdentityError::InvalidOrgRoles
+        );
+
+        let org_member = &mut ctx.accounts.org_member;
+        org_member.roles = roles;
+
+        emit!(OrgMemberUpdated {
+            organization: identity.key(),
+            member: org_member.member,
+            roles,
+            updated_by: ctx.accounts.admin.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Remove a member from an organization's roster (org controller or ADMIN member)
+    pub fn remove_org_member(ctx: Context<RemoveOrgMember>) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        require!(
+            identity.has_org_role(
+                &identity.key(),
+                &ctx.accounts.admin.key(),
+                ctx.accounts.admin_membership.as_deref(),
+                org_roles::ADMIN,
+            ),
+            errors::IdentityError::MissingOrgRole
+        );
+        if ctx.accounts.admin.key() == identity.controller() {
+            identity.last_owner_activity = Clock::get()?.unix_timestamp;
+        }
+
+        emit!(OrgMemberUpdated {
+            organization: identity.key(),
+            member: ctx.accounts.org_member.member,
+            roles: 0,
+            updated_by: ctx.accounts.admin.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Hand control of the identity to an M-of-N signer set (authority only). The
+    /// multisig_signer PDA becomes the authority; it acts only through execute_multisig.
+    pub fn enable_multisig(
+        ctx: Context<EnableMultisig>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        MultisigAuthority::validate(&signers, threshold)?;
+
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        require!(
+            identity.identity_type != IdentityType::Dependent,
+            errors::IdentityError::InvalidIdentityType
+        );
+
+        let multisig_signer = ctx.accounts.multisig_signer.key();
+        identity.authority = multisig_signer;
+        identity.pending_authority = None;
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
+
+        let authority_index = &mut ctx.accounts.new_authority_index;
+        authority_index.authority = multisig_signer;
+        authority_index.identity = identity.key();
+        authority_index.bump = ctx.bumps.new_authority_index;
+
+        ctx.accounts.did_index.authority = multisig_signer;
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.identity = identity.key();
+        multisig.signers = signers;
+        multisig.threshold = threshold;
+        multisig.action_nonce = 0;
+        multisig.signer_bump = ctx.bumps.multisig_signer;
+        multisig.bump = ctx.bumps.multisig;
+
+        emit!(MultisigUpdated {
+            identity: identity.key(),
+            multisig_signer,
+            signers: multisig.signers.clone(),
+            threshold,
+        });
+
+        Ok(())
+    }
+
+    /// Replace the signer set and threshold (multisig_signer only, via execute_multisig)
+    pub fn update_multisig(
+        ctx: Context<UpdateMultisig>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        MultisigAuthority::validate(&signers, threshold)?;
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.signers = signers;
+        multisig.threshold = threshold;
+
+        emit!(MultisigUpdated {
+            identity: multisig.identity,
+            multisig_signer: ctx.accounts.multisig_signer.key(),
+            signers: multisig.signers.clone(),
+            threshold,
+        });
+
+        Ok(())
+    }
+
+    /// Open a multisig action so approvals can be collected across transactions
+    pub fn propose_multisig_action(
+        ctx: Context<ProposeMultisigAction>,
+        instruction_hash: [u8; 32],
+    ) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        let proposer = ctx.accounts.proposer.key();
+        require!(multisig.is_signer(&proposer), errors::IdentityError::NotMultisigSigner);
+
+        let action = &mut ctx.accounts.action;
+        action.multisig = multisig.key();
+        action.proposer = proposer;
+        action.nonce = multisig.action_nonce;
+        action.instruction_hash = instruction_hash;
+        action.approvals = vec![proposer];
+        action.created_at = Clock::get()?.unix_timestamp;
+        action.bump = ctx.bumps.action;
+
+        multisig.action_nonce = multisig.action_nonce
+            .checked_add(1)
+            .ok_or(errors::IdentityError::Overflow)?;
+
+        msg!("Multisig action {} proposed by {}", action.nonce, proposer);
+
+        Ok(())
+    }
+
+    /// Approve an open multisig action (multisig signers only)
+    pub fn approve_multisig_action(ctx: Context<ApproveMultisigAction>) -> Result<()> {
+        let approver = ctx.accounts.approver.key();
+        require!(
+            ctx.accounts.multisig.is_signer(&approver),
+            errors::IdentityError::NotMultisigSigner
+        );
+
+        let action = &mut ctx.accounts.action;
+        require!(
+            !action.approvals.contains(&approver),
+            errors::IdentityError::MultisigActionAlreadyApproved
+        );
+        require!(
+            action.approvals.len() < MultisigAuthority::MAX_SIGNERS,
+            errors::IdentityError::InvalidMultisigSigners
+        );
+        action.approvals.push(approver);
+
+        msg!("Multisig action {} approved by {}", action.nonce, approver);
+
+        Ok(())
+    }
+
+    /// Withdraw an open multisig action (proposer only)
+    pub fn cancel_multisig_action(ctx: Context<CancelMultisigAction>) -> Result<()> {
+        msg!("Multisig action {} cancelled", ctx.accounts.action.nonce);
+        Ok(())
+    }
+
+    /// Invoke an authority-gated instruction of this program or the credential manager,
+    /// signed by the multisig_signer PDA. Approvals are co-signers passed after the first
+    /// `inner_account_count` remaining accounts, plus those recorded on `action` if given.
+    pub fn execute_multisig<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteMultisig<'info>>,
+        data: Vec<u8>,
+        inner_account_count: u8,
+    ) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let multisig_signer = ctx.accounts.multisig_signer.key();
+        let target_program = &ctx.accounts.target_program;
+
+        require!(
+            inner_account_count as usize <= ctx.remaining_accounts.len(),
+            errors::IdentityError::MultisigActionMismatch
+        );
+        let (inner_accounts, cosigners) = ctx.remaining_accounts.split_at(inner_account_count as usize);
+
+        let metas: Vec<AccountMeta> = inner_accounts
+            .iter()
+            .map(|info| AccountMeta {
+                pubkey: info.key(),
+                is_signer: info.is_signer || info.key() == multisig_signer,
+                is_writable: info.is_writable,
+            })
+            .collect();
+        let instruction_hash = multisig_instruction_hash(&target_program.key(), &metas, &data);
+
+        let mut approvers: Vec<Pubkey> = cosigners
+            .iter()
+            .filter(|info| info.is_signer)
+            .map(|info| info.key())
+            .collect();
+        if let Some(action) = &ctx.accounts.action {
+            require!(
+                action.instruction_hash == instruction_hash,
+                errors::IdentityError::MultisigActionMismatch
+            );
+            approvers.extend_from_slice(&action.approvals);
+        }
+        let approvals = multisig.count_approvals(&approvers);
+        require!(
+            approvals >= multisig.threshold as usize,
+            errors::IdentityError::InsufficientMultisigApprovals
+        );
+
+        let instruction = Instruction {
+            program_id: target_program.key(),
+            accounts: metas,
+            data,
+        };
+        let mut infos = inner_accounts.to_vec();
+        infos.push(target_program.to_account_info());
+
+        let identity_key = ctx.accounts.identity_account.key();
+        let seeds = &[b"multisig_signer".as_ref(), identity_key.as_ref(), &[multisig.signer_bump]];
+        invoke_signed(&instruction, &infos, &[&seeds[..]])?;
+
+        if let Some(action) = &ctx.accounts.action {
+            let proposer = ctx.accounts.proposer
+                .as_ref()
+                .filter(|p| p.key() == action.proposer)
+                .ok_or(errors::IdentityError::MultisigActionMismatch)?;
+            action.close(proposer.to_account_info())?;
+        }
+
+        emit!(MultisigExecuted {
+            identity: identity_key,
+            program_id: instruction.program_id,
+            instruction_hash,
+            approvals: approvals as u8,
+        });
+
+        Ok(())
+    }
+
+    /// Place a compliance or fraud hold on an identity (admin or compliance officer)
+    pub fn freeze_identity(
+        ctx: Context<ComplianceHold>,
+        reason_code: u16,
+        case_reference: [u8; 32],
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        let clock = Clock::get()?;
+
+        identity.status = IdentityStatus::Frozen;
+        identity.freeze_reason = reason_code;
+        identity.freeze_case = case_reference;
+        identity.frozen_at = clock.unix_timestamp;
+        identity.last_updated = clock.unix_timestamp;
+
+        emit!(IdentityFrozen {
+            identity: identity.key(),
+            reason_code,
+            case_reference,
+            frozen_by: ctx.accounts.officer.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Lift a compliance hold (admin or compliance officer)
+    pub fn unfreeze_identity(ctx: Context<ComplianceHold>) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        require!(
+            identity.status == IdentityStatus::Frozen,
+            errors::IdentityError::IdentityNotFrozen
+        );
+
+        emit!(IdentityUnfrozen {
+            identity: identity.key(),
+            case_reference: identity.freeze_case,
+            unfrozen_by: ctx.accounts.officer.key(),
+        });
+
+        identity.status = IdentityStatus::Active;
+        identity.freeze_reason = 0;
+        identity.freeze_case = [0; 32];
+        identity.frozen_at = 0;
+        identity.last_updated = Clock::get()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// Name (or clear) a nominee who may succeed to the identity after `inactivity_threshold`
+    /// seconds without owner-signed activity. Also cancels any open succession claim
+    /// (authority only).
+    pub fn set_nominee(
+        ctx: Context<SetNominee>,
+        nominee: Option<Pubkey>,
+        inactivity_threshold: i64,
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        if nominee.is_some() {
+            require!(
+                inactivity_threshold >= IdentityAccount::MIN_INACTIVITY_THRESHOLD,
+                errors::IdentityError::InvalidInactivityThreshold
+            );
+        }
+
+        identity.nominee = nominee;
+        identity.inactivity_threshold = if nominee.is_some() { inactivity_threshold } else { 0 };
+        identity.succession_started_at = 0;
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
+
+        msg!("Nominee of {} set to {:?}", identity.key(), nominee);
+
+        Ok(())
+    }
+
+    /// Open a succession claim on an inactive identity (nominee only). Any action signed
+    /// by the controller during the challenge window cancels the claim.
+    pub fn start_succession(ctx: Context<StartSuccession>) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        identity.require_no_guardian()?;
+        let clock = Clock::get()?;
+
+        require!(
+            identity.nominee == Some(ctx.accounts.nominee.key()),
+            errors::IdentityError::NotNominee
+        );
+        // A claim the owner answered by acting on the identity can be reopened later
+        require!(
+            identity.succession_started_at == 0
+                || identity.last_owner_activity > identity.succession_started_at,
+            errors::IdentityError::SuccessionAlreadyStarted
+        );
+        let inactive_since = identity.last_owner_activity
+            .checked_add(identity.inactivity_threshold)
+            .ok_or(errors::IdentityError::Overflow)?;
+        require!(
+            clock.unix_timestamp >= inactive_since,
+            errors::IdentityError::IdentityNotInactive
+        );
+
+        // last_owner_activity is left untouched so later owner actions can be detected
+        identity.succession_started_at = clock.unix_timestamp;
+
+        emit!(SuccessionStarted {
+            identity: identity.key(),
+            nominee: ctx.accounts.nominee.key(),
+            challenge_ends_at: clock.unix_timestamp + IdentityAccount::SUCCESSION_CHALLENGE_WINDOW,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw an open succession claim (authority only)
+    pub fn cancel_succession(ctx: Context<SetNominee>) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        require!(
+            identity.succession_started_at != 0,
+            errors::IdentityError::NoSuccessionClaim
+        );
+
+        identity.succession_started_at = 0;
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
+
+        msg!("Succession claim on {} cancelled", identity.key());
+
+        Ok(())
+    }
+
+    /// Take over the identity once the challenge window passed without activity (nominee only)
+    pub fn complete_succession(ctx: Context<CompleteSuccession>) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        identity.require_no_guardian()?;
+        let clock = Clock::get()?;
+
+        require!(
+            identity.succession_started_at != 0,
+            errors::IdentityError::NoSuccessionClaim
+        );
+        require!(
+            identity.last_owner_activity <= identity.succession_started_at,
+            errors::IdentityError::SuccessionCancelled
+        );
+        let challenge_ends_at = identity.succession_started_at
+            .checked_add(IdentityAccount::SUCCESSION_CHALLENGE_WINDOW)
+            .ok_or(errors::IdentityError::Overflow)?;
+        require!(
+            clock.unix_timestamp >= challenge_ends_at,
+            errors::IdentityError::ChallengeWindowNotElapsed
+        );
+
+        let old_authority = identity.authority;
+        identity.authority = ctx.accounts.nominee.key();
+        identity.pending_authority = None;
+        identity.nominee = None;
+        identity.inactivity_threshold = 0;
+        identity.succession_started_at = 0;
+        identity.last_updated = clock.unix_timestamp;
+        identity.last_owner_activity = clock.unix_timestamp;
+
+        let authority_index = &mut ctx.accounts.new_authority_index;
+        authority_index.authority = identity.authority;
+        authority_index.identity = identity.key();
+        authority_index.bump = ctx.bumps.new_authority_index;
+
+        ctx.accounts.did_index.authority = identity.authority;
+
+        emit!(SuccessionCompleted {
+            identity: identity.key(),
+            old_authority,
+            new_authority: identity.authority,
+        });
+
+        Ok(())
+    }
+
+    /// Erase personal data and leave a tombstone that keeps the DID reserved (authority only).
+    /// Freed rent goes back to the sponsor if the identity was sponsored.
+    pub fn deactivate_identity(ctx: Context<DeactivateIdentity>) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        let clock = Clock::get()?;
+
+        identity.did = String::new();
+        identity.metadata_uri = String::new();
+        identity.metadata_hash = [0; 32];
+        identity.verification_bitmap = 0;
+        identity.recovery_keys = Vec::new();
+        identity.recovery_threshold = 0;
+        identity.pending_authority = None;
+        identity.verification_methods = Vec::new();
+        identity.services = Vec::new();
+        identity.nominee = None;
+        identity.inactivity_threshold = 0;
+        identity.succession_started_at = 0;
+        // Linked wallets, delegates, consent grants and org members stay on-chain until
+        // their owners close them, but every reader rejects a deactivated parent
+        identity.status = IdentityStatus::Deactivated;
+        identity.deactivated_at = clock.unix_timestamp;
+        identity.last_updated = clock.unix_timestamp;
+
+        ctx.accounts.did_index.tombstoned_at = clock.unix_timestamp;
+
+        msg!("Identity {} deactivated", identity.key());
+
+        Ok(())
+    }
+
+    /// Close a tombstone once its retention period has passed, releasing the DID
+    pub fn purge_tombstone(ctx: Context<PurgeTombstone>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let identity = &ctx.accounts.identity_account;
+        let clock = Clock::get()?;
+
+        require!(
+            identity.status == IdentityStatus::Deactivated,
+            errors::IdentityError::IdentityNotDeactivated
+        );
+        let purgeable_at = identity.deactivated_at
+            .checked_add(config.tombstone_period)
+            .ok_or(errors::IdentityError::Overflow)?;
+        require!(
+            clock.unix_timestamp >= purgeable_at,
+            errors::IdentityError::TombstonePeriodNotElapsed
+        );
+
+        msg!("Tombstone for identity {} purged", identity.key());
+
+        Ok(())
+    }
+
+    /// Add a verification method to the DID document (authority only)
+    pub fn add_verification_method(
+        ctx: Context<AddVerificationMethod>,
+        id: String,
+        public_key: Pubkey,
+        purposes: u8,
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        require!(
+            id.len() <= VerificationMethod::MAX_ID_LEN,
+            errors::IdentityError::DocumentFieldTooLong
+        );
+        require!(
+            is_valid_purposes(purposes),
+            errors::IdentityError::InvalidVerificationMethodPurpose
+        );
+        require!(
+            identity.verification_methods.len() < IdentityAccount::MAX_VERIFICATION_METHODS,
+            errors::IdentityError::TooManyVerificationMethods
+        );
+        require!(
+            identity.find_verification_method(&id).is_none(),
+            errors::IdentityError::VerificationMethodAlreadyExists
+        );
+
+        identity.verification_methods.push(VerificationMethod {
+            id: id.clone(),
+            public_key,
+            purposes,
+        });
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
+
+        msg!("Verification method {} added with purposes {:#07b}", id, purposes);
+
+        Ok(())
+    }
+
+    /// Change the purposes of an existing verification method (authority only)
+    pub fn update_verification_method(
+        ctx: Context<UpdateDidDocument>,
+        id: String,
+        purposes: u8,
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        require!(
+            is_valid_purposes(purposes),
+            errors::IdentityError::InvalidVerificationMethodPurpose
+        );
+        let index = identity
+            .find_verification_method(&id)
+            .ok_or(errors::IdentityError::VerificationMethodNotFound)?;
+
+        identity.verification_methods[index].purposes = purposes;
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
+
+        msg!("Verification method {} updated with purposes {:#07b}", id, purposes);
+
+        Ok(())
+    }
+
+    /// Remove a verification method and shrink the account (authority only)
+    pub fn remove_verification_method(
+        ctx: Context<RemoveVerificationMethod>,
+        id: String,
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        let index = identity
+            .find_verification_method(&id)
+            .ok_or(errors::IdentityError::VerificationMethodNotFound)?;
+
+        identity.verification_methods.remove(index);
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
+
+        msg!("Verification method {} removed", id);
+
+        Ok(())
+    }
+
+    /// Add a service endpoint to the DID document (authority only)
+    pub fn add_service(
+        ctx: Context<AddService>,
+        id: String,
+        service_type: String,
+        endpoint: String,
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        require!(
+            is_valid_service(&id, &service_type, &endpoint),
+            errors::IdentityError::DocumentFieldTooLong
+        );
+        require!(
+            identity.services.len() < IdentityAccount::MAX_SERVICES,
+            errors::IdentityError::TooManyServices
+        );
+        require!(
+            identity.find_service(&id).is_none(),
+            errors::IdentityError::ServiceAlreadyExists
+        );
+
+        identity.services.push(ServiceEndpoint {
+            id: id.clone(),
+            service_type,
+            endpoint,
+        });
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
+
+        msg!("Service {} added", id);
+
+        Ok(())
+    }
+
+    /// Replace the type and endpoint of an existing service (authority only)
+    pub fn update_service(
+        ctx: Context<UpdateDidDocument>,
+        id: String,
+        service_type: String,
+        endpoint: String,
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        require!(
+            is_valid_service(&id, &service_type, &endpoint),
+            errors::IdentityError::DocumentFieldTooLong
+        );
+        let index = identity
+            .find_service(&id)
+            .ok_or(errors::IdentityError::ServiceNotFound)?;
+
+        let service = &mut identity.services[index];
+        service.service_type = service_type;
+        service.endpoint = endpoint;
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
+
+        msg!("Service {} updated", id);
+
+        Ok(())
+    }
+
+    /// Remove a service endpoint and shrink the account (authority only)
+    pub fn remove_service(
+        ctx: Context<RemoveService>,
+        id: String,
+    ) -> Result<()> {
+        let identity = &mut ctx.accounts.identity_account;
+        identity.require_active()?;
+        let index = identity
+            .find_service(&id)
+            .ok_or(errors::IdentityError::ServiceNotFound)?;
+
+        identity.services.remove(index);
+        identity.last_updated = Clock::get()?.unix_timestamp;
+        identity.last_owner_activity = identity.last_updated;
+
+        msg!("Service {} removed", id);
+
+        Ok(())
+    }
+}
+
+fn is_valid_purposes(purposes: u8) -> bool {
+    purposes != 0 && purposes & !verification_method_purposes::ALL == 0
+}
+
+fn is_valid_service(id: &str, service_type: &str, endpoint: &str) -> bool {
+    id.len() <= ServiceEndpoint::MAX_ID_LEN
+        && service_type.len() <= ServiceEndpoint::MAX_TYPE_LEN
+        && endpoint.len() <= ServiceEndpoint::MAX_ENDPOINT_LEN
+}
+
+fn is_valid_recovery_threshold(threshold: u8, recovery_key_count: usize) -> bool {
+    if recovery_key_count == 0 {
+        threshold == 0
+    } else {
+        threshold >= 1 && threshold as usize <= recovery_key_count
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + GlobalConfig::LEN,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = admin
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.pending_admin == Some(new_admin.key())
+            @ errors::IdentityError::NoPendingAdminTransfer
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    pub new_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: Pre-upgrade GlobalConfig, checked by size and discriminator in the handler
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        owner = crate::ID
+    )]
+    pub config: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(sponsor: Pubkey)]
+pub struct SetSponsor<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = admin
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + SponsorAccount::LEN,
+        seeds = [b"sponsor", sponsor.as_ref()],
+        bump
+    )]
+    pub sponsor_account: Account<'info, SponsorAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(identity_id: [u8; 32], did: String)]
+pub struct CreateIdentity<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + IdentityAccount::LEN,
+        seeds = [b"identity", identity_id.as_ref()],
+        bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + AuthorityIndex::LEN,
+        seeds = [b"authority", authority.key().as_ref()],
+        bump
+    )]
+    pub authority_index: Account<'info, AuthorityIndex>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + DidIndex::LEN,
+        seeds = [b"did", hash_did(&did).as_ref()],
+        bump
+    )]
+    pub did_index: Account<'info, DidIndex>,
+
+    /// Sponsor record of the payer; omit when the authority pays for itself
+    #[account(
+        mut,
+        seeds = [b"sponsor", payer.key().as_ref()],
+        bump = sponsor_account.bump
+    )]
+    pub sponsor_account: Option<Account<'info, SponsorAccount>>,
+
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(legacy_seed: Pubkey, identity_id: [u8; 32], did: String)]
+pub struct MigrateIdentity<'info> {
+    /// CHECK: Pre-migration identity account, deserialized as LegacyIdentityAccount in the handler.
+    /// Seeded by the original authority, which differs from `legacy.authority` after a legacy recovery.
+    #[account(
+        mut,
+        seeds = [b"identity", legacy_seed.as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub legacy_identity: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + IdentityAccount::LEN,
+        seeds = [b"identity", identity_id.as_ref()],
+        bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AuthorityIndex::LEN,
+        seeds = [b"authority", authority.key().as_ref()],
+        bump
+    )]
+    pub authority_index: Account<'info, AuthorityIndex>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + DidIndex::LEN,
+        seeds = [b"did", hash_did(&did).as_ref()],
+        bump
+    )]
+    pub did_index: Account<'info, DidIndex>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(verification_type: u8, verified: bool, assurance_level: u8, nullifier: [u8; 32])]
+pub struct UpdateVerificationStatus<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub oracle: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + VerificationRecord::LEN,
+        seeds = [b"verification", identity_account.key().as_ref(), &[verification_type]],
+        bump
+    )]
+    pub verification_record: Account<'info, VerificationRecord>,
+
+    /// CHECK: Oracle verification request that produced this result, checked against the identity in the handler
+    pub source_request: AccountInfo<'info>,
+
+    /// Required when setting the Aadhaar bit
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + AadhaarNullifier::LEN,
+        seeds = [b"nullifier", nullifier.as_ref()],
+        bump
+    )]
+    pub aadhaar_nullifier: Option<Account<'info, AadhaarNullifier>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseNullifier<'info> {
+    #[account(seeds = [b"config"], bump, has_one = admin)]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"nullifier", aadhaar_nullifier.nullifier.as_ref()],
+        bump = aadhaar_nullifier.bump,
+        close = admin
+    )]
+    pub aadhaar_nullifier: Account<'info, AadhaarNullifier>,
+
+    /// CHECK: Identity that claimed the nullifier; may already be purged
+    #[account(address = aadhaar_nullifier.identity)]
+    pub identity_account: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClearExpiredVerification<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        seeds = [
+            b"verification",
+            identity_account.key().as_ref(),
+            &[verification_record.verification_type]
+        ],
+        bump = verification_record.bump
+    )]
+    pub verification_record: Account<'info, VerificationRecord>,
+
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReputation<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub reputation_engine: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AddRecoveryKey<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageRecoveryKeys<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransferAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.pending_authority == Some(new_authority.key())
+            @ errors::IdentityError::NoPendingAuthorityTransfer
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"authority", identity_account.authority.as_ref()],
+        bump = old_authority_index.bump,
+        constraint = old_authority_index.identity == identity_account.key(),
+        close = old_authority
+    )]
+    pub old_authority_index: Account<'info, AuthorityIndex>,
+
+    /// CHECK: Receives the old authority index rent back
+    #[account(mut, address = identity_account.authority)]
+    pub old_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = new_authority,
+        space = 8 + AuthorityIndex::LEN,
+        seeds = [b"authority", new_authority.key().as_ref()],
+        bump
+    )]
+    pub new_authority_index: Account<'info, AuthorityIndex>,
+
+    #[account(
+        mut,
+        seeds = [b"did", did_index.did_hash.as_ref()],
+        bump = did_index.bump,
+        constraint = did_index.identity == identity_account.key()
+    )]
+    pub did_index: Account<'info, DidIndex>,
+
+    #[account(mut)]
+    pub new_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRecoveryThreshold<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeRecovery<'info> {
+    #[account(
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        init,
+        payer = recovery_signer,
+        space = 8 + RecoveryProposal::LEN,
+        seeds = [b"recovery", identity_account.key().as_ref(), recovery_signer.key().as_ref()],
+        bump
+    )]
+    pub recovery_proposal: Account<'info, RecoveryProposal>,
+
+    #[account(mut)]
+    pub recovery_signer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveRecovery<'info> {
+    #[account(
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"recovery", identity_account.key().as_ref(), recovery_proposal.proposer.as_ref()],
+        bump = recovery_proposal.bump
+    )]
+    pub recovery_proposal: Account<'info, RecoveryProposal>,
+
+    pub recovery_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecoverIdentity<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"recovery", identity_account.key().as_ref(), recovery_proposal.proposer.as_ref()],
+        bump = recovery_proposal.bump,
+        close = proposer
+    )]
+    pub recovery_proposal: Account<'info, RecoveryProposal>,
+
+    /// CHECK: Receives the proposal rent back
+    #[account(mut, address = recovery_proposal.proposer)]
+    pub proposer: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"authority", identity_account.authority.as_ref()],
+        bump = old_authority_index.bump,
+        constraint = old_authority_index.identity == identity_account.key(),
+        close = old_authority
+    )]
+    pub old_authority_index: Account<'info, AuthorityIndex>,
+
+    /// CHECK: Receives the old authority index rent back
+    #[account(mut, address = identity_account.authority)]
+    pub old_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + AuthorityIndex::LEN,
+        seeds = [b"authority", recovery_proposal.new_authority.as_ref()],
+        bump
+    )]
+    pub new_authority_index: Account<'info, AuthorityIndex>,
+
+    #[account(
+        mut,
+        seeds = [b"did", did_index.did_hash.as_ref()],
+        bump = did_index.bump,
+        constraint = did_index.identity == identity_account.key()
+    )]
+    pub did_index: Account<'info, DidIndex>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecovery<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"recovery", identity_account.key().as_ref(), recovery_proposal.proposer.as_ref()],
+        bump = recovery_proposal.bump,
+        close = proposer
+    )]
+    pub recovery_proposal: Account<'info, RecoveryProposal>,
+
+    /// CHECK: Receives the proposal rent back
+    #[account(mut, address = recovery_proposal.proposer)]
+    pub proposer: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateStakedAmount<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub staking_manager: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, GlobalConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(did: String)]
+pub struct ResolveDid<'info> {
+    #[account(
+        seeds = [b"did", hash_did(&did).as_ref()],
+        bump = did_index.bump
+    )]
+    pub did_index: Account<'info, DidIndex>,
+
+    #[account(address = did_index.identity)]
+    pub identity_account: Account<'info, IdentityAccount>,
+}
+
+#[derive(Accounts)]
+pub struct AddVerificationMethod<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner,
+        realloc = IdentityAccount::space(
+            identity_account.verification_methods.len() + 1,
+            identity_account.services.len()
+        ),
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveVerificationMethod<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner,
+        realloc = IdentityAccount::space(
+            identity_account.verification_methods.len().saturating_sub(1),
+            identity_account.services.len()
+        ),
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddService<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner,
+        realloc = IdentityAccount::space(
+            identity_account.verification_methods.len(),
+            identity_account.services.len() + 1
+        ),
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveService<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner,
+        realloc = IdentityAccount::space(
+            identity_account.verification_methods.len(),
+            identity_account.services.len().saturating_sub(1)
+        ),
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AssertIdentity<'info> {
+    #[account(
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    /// Supplied when asserting for a linked wallet rather than the authority
+    #[account(
+        seeds = [b"linked_wallet", linked_wallet.wallet.as_ref()],
+        bump = linked_wallet.bump,
+        constraint = linked_wallet.identity == identity_account.key()
+    )]
+    pub linked_wallet: Option<Account<'info, LinkedWallet>>,
+
+    /// Required when the identity is in strict consent mode
+    #[account(
+        seeds = [b"consent", identity_account.key().as_ref(), consent.relying_party.as_ref()],
+        bump = consent.bump
+    )]
+    pub consent: Option<Account<'info, ConsentGrant>>,
+
+    /// Relying party the consent was granted to, usually a PDA of the calling program
+    pub relying_party: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct AssertPseudonym<'info> {
+    #[account(
+        seeds = [b"pseudonym", pseudonym.relying_party.as_ref(), pseudonym.commitment.as_ref()],
+        bump = pseudonym.bump
+    )]
+    pub pseudonym: Account<'info, Pseudonym>,
+}
+
+#[derive(Accounts)]
+#[instruction(relying_party: Pubkey, commitment: [u8; 32])]
+pub struct RegisterPseudonym<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Pseudonym::LEN,
+        seeds = [b"pseudonym", relying_party.as_ref(), commitment.as_ref()],
+        bump
+    )]
+    pub pseudonym: Account<'info, Pseudonym>,
+
+    /// Pseudonym-only key; must not be the identity authority or a linked wallet
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AttestPseudonym<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.pseudonym_attester != Pubkey::default()
+            && config.pseudonym_attester == attester.key()
+            @ errors::IdentityError::UnauthorizedAttester
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"pseudonym", pseudonym.relying_party.as_ref(), pseudonym.commitment.as_ref()],
+        bump = pseudonym.bump
+    )]
+    pub pseudonym: Account<'info, Pseudonym>,
+
+    pub attester: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokePseudonym<'info> {
+    #[account(
+        mut,
+        seeds = [b"pseudonym", pseudonym.relying_party.as_ref(), pseudonym.commitment.as_ref()],
+        bump = pseudonym.bump,
+        has_one = owner,
+        close = owner
+    )]
+    pub pseudonym: Account<'info, Pseudonym>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetStrictConsent<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(relying_party: Pubkey)]
+pub struct GrantConsent<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ConsentGrant::LEN,
+        seeds = [b"consent", identity_account.key().as_ref(), relying_party.as_ref()],
+        bump
+    )]
+    pub consent: Account<'info, ConsentGrant>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeConsent<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"consent", identity_account.key().as_ref(), consent.relying_party.as_ref()],
+        bump = consent.bump,
+        close = authority
+    )]
+    pub consent: Account<'info, ConsentGrant>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    /// Identity authority, or a delegate holding UPDATE_METADATA
+    pub signer: Signer<'info>,
+
+    pub delegate: Option<Account<'info, Delegate>>,
+}
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct AddDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Delegate::LEN,
+        seeds = [b"delegate", identity_account.key().as_ref(), delegate.as_ref()],
+        bump
+    )]
+    pub delegate_account: Account<'info, Delegate>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct LinkWallet<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + LinkedWallet::LEN,
+        seeds = [b"linked_wallet", wallet.key().as_ref()],
+        bump
+    )]
+    pub linked_wallet: Account<'info, LinkedWallet>,
+
+    pub wallet: Signer<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetWalletInheritance<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"linked_wallet", linked_wallet.wallet.as_ref()],
+        bump = linked_wallet.bump,
+        constraint = linked_wallet.identity == identity_account.key()
+    )]
+    pub linked_wallet: Account<'info, LinkedWallet>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnlinkWallet<'info> {
+    #[account(
+        mut,
+        seeds = [b"linked_wallet", linked_wallet.wallet.as_ref()],
+        bump = linked_wallet.bump,
+        close = payer
+    )]
+    pub linked_wallet: Account<'info, LinkedWallet>,
+
+    /// Needed only when the controller unlinks
+    #[account(mut, address = linked_wallet.identity)]
+    pub identity_account: Option<Account<'info, IdentityAccount>>,
+
+    /// Linked wallet or identity controller
+    pub signer: Signer<'info>,
+
+    /// CHECK: Original payer of the link, receives the rent
+    #[account(mut, address = linked_wallet.payer)]
+    pub payer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"delegate", identity_account.key().as_ref(), delegate_account.delegate.as_ref()],
+        bump = delegate_account.bump,
+        close = authority
+    )]
+    pub delegate_account: Account<'info, Delegate>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AssignGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        has_one = authority
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub authority: Signer<'info>,
+
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimControl<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        has_one = authority
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterOrganization<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        has_one = authority
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(member: Pubkey)]
+pub struct AddOrgMember<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + OrgMember::LEN,
+        seeds = [b"org_member", identity_account.key().as_ref(), member.as_ref()],
+        bump
+    )]
+    pub org_member: Account<'info, OrgMember>,
+
+    /// Signer's own membership; omit when the signer is the organization's controller
+    pub admin_membership: Option<Account<'info, OrgMember>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageOrgMember<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"org_member", identity_account.key().as_ref(), org_member.member.as_ref()],
+        bump = org_member.bump
+    )]
+    pub org_member: Account<'info, OrgMember>,
+
+    pub admin_membership: Option<Account<'info, OrgMember>>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveOrgMember<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"org_member", identity_account.key().as_ref(), org_member.member.as_ref()],
+        bump = org_member.bump,
+        close = admin
+    )]
+    pub org_member: Account<'info, OrgMember>,
+
+    pub admin_membership: Option<Account<'info, OrgMember>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EnableMultisig<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        has_one = authority
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MultisigAuthority::LEN,
+        seeds = [b"multisig", identity_account.key().as_ref()],
+        bump
+    )]
+    pub multisig: Account<'info, MultisigAuthority>,
+
+    /// CHECK: Data-less PDA that becomes the identity authority
+    #[account(seeds = [b"multisig_signer", identity_account.key().as_ref()], bump)]
+    pub multisig_signer: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"authority", authority.key().as_ref()],
+        bump = old_authority_index.bump,
+        constraint = old_authority_index.identity == identity_account.key(),
+        close = authority
+    )]
+    pub old_authority_index: Account<'info, AuthorityIndex>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AuthorityIndex::LEN,
+        seeds = [b"authority", multisig_signer.key().as_ref()],
+        bump
+    )]
+    pub new_authority_index: Account<'info, AuthorityIndex>,
+
+    #[account(
+        mut,
+        seeds = [b"did", did_index.did_hash.as_ref()],
+        bump = did_index.bump,
+        constraint = did_index.identity == identity_account.key()
+    )]
+    pub did_index: Account<'info, DidIndex>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMultisig<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig", multisig.identity.as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultisigAuthority>,
+
+    #[account(
+        seeds = [b"multisig_signer", multisig.identity.as_ref()],
+        bump = multisig.signer_bump
+    )]
+    pub multisig_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeMultisigAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig", multisig.identity.as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultisigAuthority>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + MultisigAction::LEN,
+        seeds = [b"multisig_action", multisig.key().as_ref(), &multisig.action_nonce.to_le_bytes()],
+        bump
+    )]
+    pub action: Account<'info, MultisigAction>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveMultisigAction<'info> {
+    #[account(
+        seeds = [b"multisig", multisig.identity.as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultisigAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig_action", multisig.key().as_ref(), &action.nonce.to_le_bytes()],
+        bump = action.bump
+    )]
+    pub action: Account<'info, MultisigAction>,
+
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelMultisigAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig_action", action.multisig.as_ref(), &action.nonce.to_le_bytes()],
+        bump = action.bump,
+        has_one = proposer,
+        close = proposer
+    )]
+    pub action: Account<'info, MultisigAction>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteMultisig<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        seeds = [b"multisig", identity_account.key().as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, MultisigAuthority>,
+
+    /// CHECK: PDA signing the inner instruction; must also appear among the inner accounts
+    #[account(
+        seeds = [b"multisig_signer", identity_account.key().as_ref()],
+        bump = multisig.signer_bump
+    )]
+    pub multisig_signer: AccountInfo<'info>,
+
+    /// CHECK: Program invoked with the multisig signature
+    #[account(
+        executable,
+        constraint = target_program.key() == crate::ID
+            || target_program.key() == config.credential_manager
+            @ errors::IdentityError::InvalidMultisigTarget
+    )]
+    pub target_program: AccountInfo<'info>,
+
+    /// Approvals collected across transactions; closed once executed
+    #[account(
+        mut,
+        seeds = [b"multisig_action", multisig.key().as_ref(), &action.nonce.to_le_bytes()],
+        bump = action.bump
+    )]
+    pub action: Option<Account<'info, MultisigAction>>,
+
+    /// CHECK: Proposer of `action`, receives its rent back
+    #[account(mut)]
+    pub proposer: Option<AccountInfo<'info>>,
+
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ComplianceHold<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.is_compliance_authority(&officer.key())
+            @ errors::IdentityError::UnauthorizedComplianceOfficer
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub officer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetNominee<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct StartSuccession<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub nominee: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CompleteSuccession<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.nominee == Some(nominee.key()) @ errors::IdentityError::NotNominee
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"authority", identity_account.authority.as_ref()],
+        bump = old_authority_index.bump,
+        constraint = old_authority_index.identity == identity_account.key(),
+        close = old_authority
+    )]
+    pub old_authority_index: Account<'info, AuthorityIndex>,
+
+    /// CHECK: Receives the old authority index rent back
+    #[account(mut, address = identity_account.authority)]
+    pub old_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = nominee,
+        space = 8 + AuthorityIndex::LEN,
+        seeds = [b"authority", nominee.key().as_ref()],
+        bump
+    )]
+    pub new_authority_index: Account<'info, AuthorityIndex>,
+
+    #[account(
+        mut,
+        seeds = [b"did", did_index.did_hash.as_ref()],
+        bump = did_index.bump,
+        constraint = did_index.identity == identity_account.key()
+    )]
+    pub did_index: Account<'info, DidIndex>,
+
+    #[account(mut)]
+    pub nominee: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeactivateIdentity<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner,
+        realloc = IdentityAccount::TOMBSTONE_SPACE,
+        realloc::payer = rent_recipient,
+        realloc::zero = false
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"authority", identity_account.authority.as_ref()],
+        bump = authority_index.bump,
+        constraint = authority_index.identity == identity_account.key(),
+        close = rent_recipient
+    )]
+    pub authority_index: Account<'info, AuthorityIndex>,
+
+    #[account(
+        mut,
+        seeds = [b"did", did_index.did_hash.as_ref()],
+        bump = did_index.bump,
+        constraint = did_index.identity == identity_account.key()
+    )]
+    pub did_index: Account<'info, DidIndex>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK: Sponsor that funded the identity, or the authority itself; receives the freed rent
+    #[account(
+        mut,
+        address = identity_account.rent_recipient() @ errors::IdentityError::InvalidRentRecipient
+    )]
+    pub rent_recipient: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PurgeTombstone<'info> {
+    #[account(seeds = [b"config"], bump)]
     pub config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        close = rent_recipient
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"did", did_index.did_hash.as_ref()],
+        bump = did_index.bump,
+        constraint = did_index.identity == identity_account.key(),
+        close = rent_recipient
+    )]
+    pub did_index: Account<'info, DidIndex>,
+
+    /// CHECK: Sponsor that funded the identity, or its last authority; receives the remaining rent
+    #[account(
+        mut,
+        address = identity_account.rent_recipient() @ errors::IdentityError::InvalidRentRecipient
+    )]
+    pub rent_recipient: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateDidDocument<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity", identity_account.identity_id.as_ref()],
+        bump = identity_account.bump,
+        constraint = identity_account.controller() == authority.key()
+            @ errors::IdentityError::UnauthorizedSigner
+    )]
+    pub identity_account: Account<'info, IdentityAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovery_threshold_is_zero_without_recovery_keys() {
+        assert!(is_valid_recovery_threshold(0, 0));
+        assert!(!is_valid_recovery_threshold(1, 0));
+    }
+
+    #[test]
+    fn recovery_threshold_is_bounded_by_recovery_keys() {
+        assert!(!is_valid_recovery_threshold(0, 3));
+        assert!(is_valid_recovery_threshold(1, 3));
+        assert!(is_valid_recovery_threshold(3, 3));
+        assert!(!is_valid_recovery_threshold(4, 3));
+    }
+
+    #[test]
+    fn verification_method_purposes_must_be_known_and_non_empty() {
+        assert!(!is_valid_purposes(0));
+        assert!(is_valid_purposes(verification_method_purposes::AUTHENTICATION));
+        assert!(is_valid_purposes(verification_method_purposes::ALL));
+        assert!(!is_valid_purposes(verification_method_purposes::ALL + 1));
+    }
 }
@@ -1,8 +1,13 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::{hash, hashv};
+use anchor_lang::solana_program::instruction::AccountMeta;
+
+use crate::errors::IdentityError;
 
 #[account]
 pub struct IdentityAccount {
     pub authority: Pubkey,           // 32
+    pub identity_id: [u8; 32],       // 32
     pub did: String,                 // 4 + 128
     pub verification_bitmap: u64,    // 8
     pub reputation_score: u64,       // 8
@@ -10,14 +15,593 @@ pub struct IdentityAccount {
     pub created_at: i64,             // 8
     pub last_updated: i64,           // 8
     pub metadata_uri: String,        // 4 + 256
-    pub recovery_keys: Vec<Pubkey>,  // 4 + (5 * 32)
+    pub recovery_keys: Vec<RecoveryKey>, // 4 + (5 * 40)
+    pub recovery_threshold: u8,      // 1
+    pub pending_authority: Option<Pubkey>, // 1 + 32
+    pub verification_methods: Vec<VerificationMethod>, // 4 + (n * VerificationMethod::LEN)
+    pub services: Vec<ServiceEndpoint>, // 4 + (n * ServiceEndpoint::LEN)
+    pub metadata_hash: [u8; 32],     // 32
+    pub metadata_revision: u32,      // 4
+    pub status: IdentityStatus,      // 1
+    pub deactivated_at: i64,         // 8
+    pub sponsor: Option<Pubkey>,     // 1 + 32
+    pub identity_type: IdentityType, // 1
+    pub guardian: Option<Pubkey>,    // 1 + 32 (in control instead of the authority while set)
+    pub guardian_until: i64,         // 8 (earliest time the dependent can claim control)
+    pub freeze_reason: u16,          // 2
+    pub freeze_case: [u8; 32],       // 32 (case reference of the current freeze)
+    pub frozen_at: i64,              // 8
+    pub nominee: Option<Pubkey>,     // 1 + 32
+    pub inactivity_threshold: i64,   // 8
+    pub succession_started_at: i64,  // 8 (0 = no claim open)
+    pub last_owner_activity: i64,    // 8 (last action signed by the controller)
+    pub strict_consent: bool,        // 1 (relying parties need a ConsentGrant)
     pub bump: u8,                    // 1
 }
 
 impl IdentityAccount {
+    pub const MAX_RECOVERY_KEYS: usize = 5;
+    /// Time before a newly added recovery key can take part in recovery
+    pub const RECOVERY_KEY_COOLDOWN: i64 = 7 * 24 * 60 * 60;
+    pub const MAX_VERIFICATION_METHODS: usize = 10;
+    pub const MAX_SERVICES: usize = 5;
+    /// Shortest inactivity period after which a nominee may claim the identity
+    pub const MIN_INACTIVITY_THRESHOLD: i64 = 90 * 24 * 60 * 60;
+    /// Time the owner has to respond to a succession claim
+    pub const SUCCESSION_CHALLENGE_WINDOW: i64 = 30 * 24 * 60 * 60;
+    /// Size with an empty DID document; entries are added through realloc
+    pub const LEN: usize = 32 + 32 + 132 + 8 + 8 + 8 + 8 + 8 + 260 + 204 + 1 + 33 + 4 + 4 + 32 + 4 + 1 + 8 + 33 + 1 + 33 + 8 + 2 + 32 + 8 + 33 + 8 + 8 + 8 + 1 + 1;
+    /// Size once deactivation has emptied did, metadata_uri and recovery_keys
+    pub const TOMBSTONE_SPACE: usize = 8 + Self::LEN - 128 - 256 - (5 * 40);
+
+    /// Account size (including discriminator) for a DID document with the given entry counts
+    pub fn space(verification_methods: usize, services: usize) -> usize {
+        8 + Self::LEN
+            + verification_methods * VerificationMethod::LEN
+            + services * ServiceEndpoint::LEN
+    }
+
+    pub fn find_recovery_key(&self, key: &Pubkey) -> Option<usize> {
+        self.recovery_keys.iter().position(|k| k.key == *key)
+    }
+
+    pub fn is_active_recovery_key(&self, key: &Pubkey, now: i64) -> bool {
+        self.recovery_keys.iter().any(|k| k.key == *key && now >= k.active_at)
+    }
+
+    /// Fails unless the identity is neither frozen nor deactivated
+    pub fn require_active(&self) -> Result<()> {
+        match self.status {
+            IdentityStatus::Active => Ok(()),
+            IdentityStatus::Frozen => err!(IdentityError::IdentityFrozen),
+            IdentityStatus::Deactivated => err!(IdentityError::IdentityDeactivated),
+        }
+    }
+
+    /// Account that receives reclaimed rent: the sponsor that funded creation, else the authority
+    pub fn rent_recipient(&self) -> Pubkey {
+        self.sponsor.unwrap_or(self.authority)
+    }
+
+    pub fn require_not_deactivated(&self) -> Result<()> {
+        require!(
+            self.status != IdentityStatus::Deactivated,
+            IdentityError::IdentityDeactivated
+        );
+        Ok(())
+    }
+
+    /// Fails while a guardian is in control; the dependent's authority key must survive
+    /// until claim_control
+    pub fn require_no_guardian(&self) -> Result<()> {
+        require!(self.guardian.is_none(), IdentityError::GuardianControlled);
+        Ok(())
+    }
+
+    /// Key that performs authority actions: the guardian while one is set, else the authority
+    pub fn controller(&self) -> Pubkey {
+        self.guardian.unwrap_or(self.authority)
+    }
+
+    /// True if `signer` is the controller, or holds a live delegation on this identity
+    /// covering `permission` granted by the current controller. `identity_key` is the
+    /// address of this account. Nobody acts for a deactivated identity, whatever
+    /// delegations it left behind.
+    pub fn is_authorized(
+        &self,
+        identity_key: &Pubkey,
+        signer: &Pubkey,
+        delegate: Option<&Delegate>,
+        permission: u8,
+        now: i64,
+    ) -> bool {
+        if self.status == IdentityStatus::Deactivated {
+            return false;
+        }
+        if *signer == self.controller() {
+            return true;
+        }
+        delegate.is_some_and(|d| d.allows(identity_key, &self.controller(), signer, permission, now))
+    }
+
+    /// True if this is an organization and `signer` is its controller, or a member
+    /// holding `role`. `identity_key` is the address of this account. A deactivated
+    /// organization's roster grants nothing.
+    pub fn has_org_role(
+        &self,
+        identity_key: &Pubkey,
+        signer: &Pubkey,
+        membership: Option<&OrgMember>,
+        role: u8,
+    ) -> bool {
+        if self.identity_type != IdentityType::Organization
+            || self.status == IdentityStatus::Deactivated
+        {
+            return false;
+        }
+        if *signer == self.controller() {
+            return true;
+        }
+        membership.is_some_and(|m| m.has_role(identity_key, signer, role))
+    }
+
+    pub fn find_verification_method(&self, id: &str) -> Option<usize> {
+        self.verification_methods.iter().position(|m| m.id == id)
+    }
+
+    pub fn find_service(&self, id: &str) -> Option<usize> {
+        self.services.iter().position(|s| s.id == id)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdentityStatus {
+    Active,
+    Frozen,
+    Deactivated,
+}
+
+/// Individuals control their own identity; dependents are run by a guardian until handover;
+/// organizations act through a roster of OrgMember accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdentityType {
+    Individual,
+    Dependent,
+    Organization,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveryKey {
+    pub key: Pubkey,                 // 32
+    pub active_at: i64,              // 8
+}
+
+/// DID document verification method (Ed25519 key with a set of purposes)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct VerificationMethod {
+    pub id: String,                  // 4 + 32
+    pub public_key: Pubkey,          // 32
+    pub purposes: u8,                // 1
+}
+
+impl VerificationMethod {
+    pub const MAX_ID_LEN: usize = 32;
+    pub const LEN: usize = 4 + Self::MAX_ID_LEN + 32 + 1;
+}
+
+/// DID document service endpoint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ServiceEndpoint {
+    pub id: String,                  // 4 + 32
+    pub service_type: String,        // 4 + 64
+    pub endpoint: String,            // 4 + 256
+}
+
+impl ServiceEndpoint {
+    pub const MAX_ID_LEN: usize = 32;
+    pub const MAX_TYPE_LEN: usize = 64;
+    pub const MAX_ENDPOINT_LEN: usize = 256;
+    pub const LEN: usize = 4 + Self::MAX_ID_LEN + 4 + Self::MAX_TYPE_LEN + 4 + Self::MAX_ENDPOINT_LEN;
+}
+
+/// Verification relationships a method can be used for (bitmask)
+pub mod verification_method_purposes {
+    pub const AUTHENTICATION: u8 = 1 << 0;
+    pub const ASSERTION_METHOD: u8 = 1 << 1;
+    pub const KEY_AGREEMENT: u8 = 1 << 2;
+    pub const CAPABILITY_INVOCATION: u8 = 1 << 3;
+    pub const CAPABILITY_DELEGATION: u8 = 1 << 4;
+    pub const ALL: u8 = AUTHENTICATION
+        | ASSERTION_METHOD
+        | KEY_AGREEMENT
+        | CAPABILITY_INVOCATION
+        | CAPABILITY_DELEGATION;
+}
+
+/// Layout of IdentityAccount before identities were keyed by identity_id,
+/// kept only so migrate_identity can read accounts at [b"identity", authority]
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyIdentityAccount {
+    pub authority: Pubkey,
+    pub did: String,
+    pub verification_bitmap: u64,
+    pub reputation_score: u64,
+    pub staked_amount: u64,
+    pub created_at: i64,
+    pub last_updated: i64,
+    pub metadata_uri: String,
+    pub recovery_keys: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl LegacyIdentityAccount {
+    /// Fixed size legacy identities were allocated with; current identities and tombstones never match it
     pub const LEN: usize = 32 + 132 + 8 + 8 + 8 + 8 + 8 + 260 + 164 + 1;
 }
 
+/// Lets a wallet find its identity now that identity PDAs no longer derive from the authority
+#[account]
+pub struct AuthorityIndex {
+    pub authority: Pubkey,           // 32
+    pub identity: Pubkey,            // 32
+    pub bump: u8,                    // 1
+}
+
+impl AuthorityIndex {
+    pub const LEN: usize = 32 + 32 + 1;
+}
+
+/// Compact identity status returned by assert_identity
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IdentityAssertion {
+    pub identity: Pubkey,
+    pub authority: Pubkey,
+    /// Wallet the assertion was made for: the authority or a linked wallet
+    pub wallet: Pubkey,
+    pub verification_bitmap: u64,
+    pub reputation_score: u64,
+    pub staked_amount: u64,
+    pub status: IdentityStatus,
+}
+
+/// Key allowed to act for an identity within a permission mask until it expires. Only
+/// honoured while the controller that granted it is still in control.
+#[account]
+pub struct Delegate {
+    pub identity: Pubkey,            // 32
+    pub delegate: Pubkey,            // 32
+    pub permissions: u8,             // 1
+    pub expires_at: i64,             // 8
+    pub created_at: i64,             // 8
+    pub granted_by: Pubkey,          // 32 (controller at the time of granting)
+    pub bump: u8,                    // 1
+}
+
+impl Delegate {
+    pub const LEN: usize = 32 + 32 + 1 + 8 + 8 + 32 + 1;
+
+    pub fn allows(
+        &self,
+        identity: &Pubkey,
+        controller: &Pubkey,
+        signer: &Pubkey,
+        permission: u8,
+        now: i64,
+    ) -> bool {
+        self.identity == *identity
+            && self.granted_by == *controller
+            && self.delegate == *signer
+            && self.permissions & permission == permission
+            && now < self.expires_at
+    }
+}
+
+/// Actions a delegate can be allowed to perform (bitmask). Recovery and
+/// authority transfer are deliberately not delegable.
+pub mod delegate_permissions {
+    pub const REQUEST_VERIFICATION: u8 = 1 << 0;
+    pub const PRESENT_CREDENTIALS: u8 = 1 << 1;
+    pub const UPDATE_METADATA: u8 = 1 << 2;
+    pub const ALL: u8 = REQUEST_VERIFICATION | PRESENT_CREDENTIALS | UPDATE_METADATA;
+}
+
+/// Member of an organization identity and the roles it holds
+#[account]
+pub struct OrgMember {
+    pub organization: Pubkey,        // 32
+    pub member: Pubkey,              // 32
+    pub roles: u8,                   // 1
+    pub added_at: i64,               // 8
+    pub bump: u8,                    // 1
+}
+
+impl OrgMember {
+    pub const LEN: usize = 32 + 32 + 1 + 8 + 1;
+
+    pub fn has_role(&self, organization: &Pubkey, member: &Pubkey, role: u8) -> bool {
+        self.organization == *organization
+            && self.member == *member
+            && self.roles & role == role
+    }
+}
+
+/// Roles an organization member can hold (bitmask)
+pub mod org_roles {
+    pub const ADMIN: u8 = 1 << 0;
+    pub const ISSUER_OPERATOR: u8 = 1 << 1;
+    pub const ORACLE_OPERATOR: u8 = 1 << 2;
+    pub const ALL: u8 = ADMIN | ISSUER_OPERATOR | ORACLE_OPERATOR;
+}
+
+/// Per-relying-party pseudonym carrying an attestation of the root identity. The link to
+/// the root is only the commitment hash(identity || relying_party || salt), which the owner
+/// opens to the pseudonym attester off-chain; no transaction touches both accounts.
+#[account]
+pub struct Pseudonym {
+    pub relying_party: Pubkey,       // 32
+    pub commitment: [u8; 32],        // 32
+    pub owner: Pubkey,               // 32 (pseudonym-only key, unrelated to the root authority)
+    pub verification_bitmap: u64,    // 8
+    pub reputation_score: u64,       // 8
+    pub status: IdentityStatus,      // 1 (root status at attestation)
+    pub attested_at: i64,            // 8 (0 = never attested)
+    pub expires_at: i64,             // 8
+    pub bump: u8,                    // 1
+}
+
+impl Pseudonym {
+    /// Longest an attestation stays usable, bounding how stale a root freeze,
+    /// deactivation or verification expiry can be
+    pub const MAX_ATTESTATION_PERIOD: i64 = 24 * 60 * 60;
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 1 + 8 + 8 + 1;
+}
+
+/// Commitment binding a pseudonym to its root identity; computed off-chain by the owner
+pub fn pseudonym_commitment(identity: &Pubkey, relying_party: &Pubkey, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[identity.as_ref(), relying_party.as_ref(), salt]).to_bytes()
+}
+
+/// Answer returned by assert_pseudonym; carries no root identity data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PseudonymAssertion {
+    pub pseudonym: Pubkey,
+    pub relying_party: Pubkey,
+    pub verification_bitmap: u64,
+    pub reputation_score: u64,
+    pub attested_at: i64,
+    pub expires_at: i64,
+}
+
+/// Owner's consent for a relying party to rely on some of the identity's verifications
+#[account]
+pub struct ConsentGrant {
+    pub identity: Pubkey,            // 32
+    pub relying_party: Pubkey,       // 32
+    pub verification_mask: u64,      // 8 (verification bits the relying party may check, plus consent_scopes)
+    pub purpose: u16,                // 2
+    pub granted_at: i64,             // 8
+    pub expires_at: i64,             // 8
+    pub bump: u8,                    // 1
+}
+
+impl ConsentGrant {
+    pub const LEN: usize = 32 + 32 + 8 + 2 + 8 + 8 + 1;
+
+    /// True if the grant is live and covers every bit in `verifications`
+    pub fn allows(&self, identity: &Pubkey, relying_party: &Pubkey, verifications: u64, now: i64) -> bool {
+        self.identity == *identity
+            && self.relying_party == *relying_party
+            && self.verification_mask & verifications == verifications
+            && now < self.expires_at
+    }
+}
+
+/// Consent scopes beyond verification types, carried in the top bits of a grant's
+/// verification_mask where no verification type bit lives
+pub mod consent_scopes {
+    /// Relying party may verify credentials held by the identity
+    pub const CREDENTIAL_VERIFICATION: u64 = 1 << 63;
+}
+
+/// Secondary wallet linked to an identity; the PDA is keyed by the wallet so it
+/// doubles as the wallet -> identity reverse lookup
+#[account]
+pub struct LinkedWallet {
+    pub identity: Pubkey,            // 32
+    pub wallet: Pubkey,              // 32
+    pub inherits_verification: bool, // 1
+    pub linked_at: i64,              // 8
+    pub payer: Pubkey,               // 32 (refunded on unlink)
+    pub bump: u8,                    // 1
+}
+
+impl LinkedWallet {
+    pub const LEN: usize = 32 + 32 + 1 + 8 + 32 + 1;
+}
+
+/// Per-identity, per-type record backing a bit in verification_bitmap
+#[account]
+pub struct VerificationRecord {
+    pub identity: Pubkey,            // 32
+    pub verification_type: u8,       // 1
+    pub verified_at: i64,            // 8 (0 if not verified)
+    pub expires_at: i64,             // 8 (0 = never expires)
+    pub source_request: Pubkey,      // 32
+    pub assurance_level: u8,         // 1
+    pub bump: u8,                    // 1
+}
+
+impl VerificationRecord {
+    pub const LEN: usize = 32 + 1 + 8 + 8 + 32 + 1 + 1;
+}
+
+/// Maps a DID to its identity; the PDA address enforces global DID uniqueness
+#[account]
+pub struct DidIndex {
+    pub did_hash: [u8; 32],          // 32
+    pub identity: Pubkey,            // 32
+    pub authority: Pubkey,           // 32
+    pub tombstoned_at: i64,          // 8 (0 while the identity is live)
+    pub bump: u8,                    // 1
+}
+
+impl DidIndex {
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 1;
+}
+
+/// Approved sponsor that pays rent for identities created on behalf of users
+#[account]
+pub struct SponsorAccount {
+    pub sponsor: Pubkey,             // 32
+    pub budget: u64,                 // 8 (lamports the sponsor may spend on rent)
+    pub spent: u64,                  // 8
+    pub rate_limit: u32,             // 4 (max identities per rate_period, 0 = unlimited)
+    pub rate_period: i64,            // 8
+    pub period_start: i64,           // 8
+    pub period_count: u32,           // 4
+    pub identities_sponsored: u64,   // 8
+    pub active: bool,                // 1
+    pub bump: u8,                    // 1
+}
+
+impl SponsorAccount {
+    pub const LEN: usize = 32 + 8 + 8 + 4 + 8 + 8 + 4 + 8 + 1 + 1;
+
+    /// Charge one sponsored creation costing `rent` lamports against the budget and rate limit
+    pub fn charge(&mut self, rent: u64, now: i64) -> Result<()> {
+        require!(self.active, IdentityError::SponsorInactive);
+
+        let spent = self.spent.checked_add(rent).ok_or(IdentityError::Overflow)?;
+        require!(spent <= self.budget, IdentityError::SponsorBudgetExceeded);
+
+        if self.rate_limit > 0 {
+            if now >= self.period_start.saturating_add(self.rate_period) {
+                self.period_start = now;
+                self.period_count = 0;
+            }
+            require!(self.period_count < self.rate_limit, IdentityError::SponsorRateLimited);
+            self.period_count += 1;
+        }
+
+        self.spent = spent;
+        self.identities_sponsored = self.identities_sponsored
+            .checked_add(1)
+            .ok_or(IdentityError::Overflow)?;
+        Ok(())
+    }
+}
+
+/// Claim on an Aadhaar number, keyed by an oracle-supplied salted hash so that one
+/// Aadhaar holder can back at most one verified identity
+#[account]
+pub struct AadhaarNullifier {
+    pub nullifier: [u8; 32],         // 32
+    pub identity: Pubkey,            // 32
+    pub claimed_at: i64,             // 8
+    pub bump: u8,                    // 1
+}
+
+impl AadhaarNullifier {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+    /// Verification bit whose setting claims the nullifier
+    pub const VERIFICATION_TYPE: u8 = 0;
+}
+
+/// SHA-256 of the DID string, used as the DidIndex seed
+pub fn hash_did(did: &str) -> [u8; 32] {
+    hash(did.as_bytes()).to_bytes()
+}
+
+/// Pending M-of-N recovery of an identity, opened by one of its recovery keys
+#[account]
+pub struct RecoveryProposal {
+    pub identity: Pubkey,            // 32
+    pub proposer: Pubkey,            // 32
+    pub new_authority: Pubkey,       // 32
+    pub approvals: Vec<Pubkey>,      // 4 + (5 * 32)
+    pub created_at: i64,             // 8
+    pub executable_at: i64,          // 8
+    pub bump: u8,                    // 1
+}
+
+impl RecoveryProposal {
+    /// Window during which the current authority can veto a recovery
+    pub const RECOVERY_DELAY: i64 = 48 * 60 * 60;
+    pub const LEN: usize = 32 + 32 + 32 + 164 + 8 + 8 + 1;
+}
+
+/// M-of-N signer set controlling an identity. The identity's authority is the
+/// data-less `multisig_signer` PDA, which only signs through execute_multisig.
+#[account]
+pub struct MultisigAuthority {
+    pub identity: Pubkey,            // 32
+    pub signers: Vec<Pubkey>,        // 4 + (10 * 32)
+    pub threshold: u8,               // 1
+    pub action_nonce: u64,           // 8
+    pub signer_bump: u8,             // 1
+    pub bump: u8,                    // 1
+}
+
+impl MultisigAuthority {
+    pub const MAX_SIGNERS: usize = 10;
+    pub const LEN: usize = 32 + 324 + 1 + 8 + 1 + 1;
+
+    /// Validate a signer set and threshold before storing them
+    pub fn validate(signers: &[Pubkey], threshold: u8) -> Result<()> {
+        require!(
+            !signers.is_empty() && signers.len() <= Self::MAX_SIGNERS,
+            IdentityError::InvalidMultisigSigners
+        );
+        require!(
+            signers.iter().enumerate().all(|(i, key)| !signers[..i].contains(key)),
+            IdentityError::InvalidMultisigSigners
+        );
+        require!(
+            threshold >= 1 && threshold as usize <= signers.len(),
+            IdentityError::InvalidMultisigThreshold
+        );
+        Ok(())
+    }
+
+    pub fn is_signer(&self, key: &Pubkey) -> bool {
+        self.signers.contains(key)
+    }
+
+    /// Number of distinct current signers among `approvers`
+    pub fn count_approvals(&self, approvers: &[Pubkey]) -> usize {
+        self.signers.iter().filter(|s| approvers.contains(s)).count()
+    }
+}
+
+/// Instruction awaiting approvals from an identity's multisig across transactions
+#[account]
+pub struct MultisigAction {
+    pub multisig: Pubkey,            // 32
+    pub proposer: Pubkey,            // 32
+    pub nonce: u64,                  // 8
+    pub instruction_hash: [u8; 32],  // 32
+    pub approvals: Vec<Pubkey>,      // 4 + (10 * 32)
+    pub created_at: i64,             // 8
+    pub bump: u8,                    // 1
+}
+
+impl MultisigAction {
+    pub const LEN: usize = 32 + 32 + 8 + 32 + 324 + 8 + 1;
+}
+
+/// Hash binding a multisig action to its target program, accounts and data
+pub fn multisig_instruction_hash(program_id: &Pubkey, accounts: &[AccountMeta], data: &[u8]) -> [u8; 32] {
+    let metas: Vec<u8> = accounts
+        .iter()
+        .flat_map(|meta| {
+            let mut bytes = meta.pubkey.to_bytes().to_vec();
+            bytes.push(meta.is_writable as u8);
+            bytes
+        })
+        .collect();
+    hashv(&[program_id.as_ref(), &metas, data]).to_bytes()
+}
+
 #[account]
 pub struct GlobalConfig {
     pub admin: Pubkey,               // 32
@@ -27,8 +611,189 @@ pub struct GlobalConfig {
     pub staking_manager: Pubkey,     // 32
     pub min_stake_amount: u64,       // 8
     pub verification_fee: u64,       // 8
+    pub tombstone_period: i64,       // 8
+    pub pending_admin: Option<Pubkey>, // 1 + 32
+    pub verification_validity: [i64; 8], // 8 * 8
+    pub compliance_officer: Pubkey,  // 32 (default = admin only)
+    pub pseudonym_attester: Pubkey,  // 32 (default = pseudonyms cannot be attested)
 }
 
 impl GlobalConfig {
-    pub const LEN: usize = 32 + 32 + 32 + 32 + 32 + 8 + 8;
+    /// Verification types (0..8) whose validity period can be configured; others never expire
+    pub const EXPIRING_VERIFICATION_TYPES: usize = 8;
+    pub const LEN: usize = 32 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 33 + (8 * Self::EXPIRING_VERIFICATION_TYPES) + 32 + 32;
+
+    /// Size of configs created before the tombstone period and the fields after it were added
+    pub const LEGACY_LEN: usize = 32 + 32 + 32 + 32 + 32 + 8 + 8;
+
+    /// Validity period in seconds for a verification type (0 = never expires)
+    pub fn verification_validity_for(&self, verification_type: u8) -> i64 {
+        self.verification_validity
+            .get(verification_type as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Admin and the designated compliance officer may freeze identities
+    pub fn is_compliance_authority(&self, key: &Pubkey) -> bool {
+        *key == self.admin || (self.compliance_officer != Pubkey::default() && *key == self.compliance_officer)
+    }
+}
+
+/// Optional GlobalConfig changes accepted by update_config
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ConfigUpdate {
+    pub verification_oracle: Option<Pubkey>,
+    pub credential_manager: Option<Pubkey>,
+    pub reputation_engine: Option<Pubkey>,
+    pub staking_manager: Option<Pubkey>,
+    pub min_stake_amount: Option<u64>,
+    pub verification_fee: Option<u64>,
+    pub tombstone_period: Option<i64>,
+    pub compliance_officer: Option<Pubkey>,
+    pub pseudonym_attester: Option<Pubkey>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consent_covers_only_granted_bits_until_expiry() {
+        let (identity, relying_party) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let grant = ConsentGrant {
+            identity,
+            relying_party,
+            verification_mask: 0b0110,
+            purpose: 1,
+            granted_at: 0,
+            expires_at: 1_000,
+            bump: 255,
+        };
+
+        assert!(grant.allows(&identity, &relying_party, 0b0010, 999));
+        assert!(grant.allows(&identity, &relying_party, 0b0110, 0));
+        assert!(!grant.allows(&identity, &relying_party, 0b0011, 0));
+        assert!(!grant.allows(&identity, &relying_party, 0b0010, 1_000));
+        assert!(!grant.allows(&identity, &Pubkey::new_unique(), 0b0010, 0));
+        assert!(!grant.allows(&Pubkey::new_unique(), &relying_party, 0b0010, 0));
+        assert!(!grant.allows(&identity, &relying_party, consent_scopes::CREDENTIAL_VERIFICATION, 0));
+    }
+
+    fn delegate(identity: Pubkey, controller: Pubkey, key: Pubkey) -> Delegate {
+        Delegate {
+            identity,
+            delegate: key,
+            permissions: delegate_permissions::UPDATE_METADATA,
+            expires_at: 1_000,
+            created_at: 0,
+            granted_by: controller,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn multisig_signer_set_must_be_non_empty_unique_and_bounded() {
+        let keys: Vec<Pubkey> = (0..=MultisigAuthority::MAX_SIGNERS).map(|_| Pubkey::new_unique()).collect();
+
+        assert!(MultisigAuthority::validate(&keys[..2], 2).is_ok());
+        assert_eq!(
+            MultisigAuthority::validate(&[], 1).unwrap_err(),
+            IdentityError::InvalidMultisigSigners.into()
+        );
+        assert_eq!(
+            MultisigAuthority::validate(&keys, 1).unwrap_err(),
+            IdentityError::InvalidMultisigSigners.into()
+        );
+        assert_eq!(
+            MultisigAuthority::validate(&[keys[0], keys[1], keys[0]], 1).unwrap_err(),
+            IdentityError::InvalidMultisigSigners.into()
+        );
+    }
+
+    #[test]
+    fn multisig_threshold_is_bounded_by_signer_count() {
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+
+        assert!(MultisigAuthority::validate(&keys, 1).is_ok());
+        assert!(MultisigAuthority::validate(&keys, 3).is_ok());
+        assert_eq!(
+            MultisigAuthority::validate(&keys, 0).unwrap_err(),
+            IdentityError::InvalidMultisigThreshold.into()
+        );
+        assert_eq!(
+            MultisigAuthority::validate(&keys, 4).unwrap_err(),
+            IdentityError::InvalidMultisigThreshold.into()
+        );
+    }
+
+    fn sponsor(budget: u64, rate_limit: u32, rate_period: i64) -> SponsorAccount {
+        SponsorAccount {
+            sponsor: Pubkey::new_unique(),
+            budget,
+            spent: 0,
+            rate_limit,
+            rate_period,
+            period_start: 0,
+            period_count: 0,
+            identities_sponsored: 0,
+            active: true,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn sponsor_charge_stops_at_budget() {
+        let mut account = sponsor(250, 0, 0);
+
+        assert!(account.charge(100, 0).is_ok());
+        assert!(account.charge(100, 0).is_ok());
+        assert_eq!(account.charge(100, 0).unwrap_err(), IdentityError::SponsorBudgetExceeded.into());
+        assert_eq!(account.spent, 200);
+        assert_eq!(account.identities_sponsored, 2);
+    }
+
+    #[test]
+    fn sponsor_charge_resets_rate_limit_each_period() {
+        let mut account = sponsor(u64::MAX, 2, 60);
+
+        assert!(account.charge(1, 0).is_ok());
+        assert!(account.charge(1, 59).is_ok());
+        assert_eq!(account.charge(1, 59).unwrap_err(), IdentityError::SponsorRateLimited.into());
+        assert!(account.charge(1, 60).is_ok());
+        assert_eq!(account.period_start, 60);
+        assert_eq!(account.period_count, 1);
+        // A rejected charge spends nothing
+        assert_eq!(account.spent, 3);
+    }
+
+    #[test]
+    fn inactive_sponsor_cannot_be_charged() {
+        let mut account = sponsor(u64::MAX, 0, 0);
+        account.active = false;
+
+        assert_eq!(account.charge(1, 0).unwrap_err(), IdentityError::SponsorInactive.into());
+    }
+
+    #[test]
+    fn delegate_allows_granted_permission_until_expiry() {
+        let (identity, controller, key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let grant = delegate(identity, controller, key);
+
+        assert!(grant.allows(&identity, &controller, &key, delegate_permissions::UPDATE_METADATA, 999));
+        assert!(!grant.allows(&identity, &controller, &key, delegate_permissions::UPDATE_METADATA, 1_000));
+        assert!(!grant.allows(&identity, &controller, &key, delegate_permissions::REQUEST_VERIFICATION, 0));
+    }
+
+    #[test]
+    fn delegate_is_bound_to_identity_signer_and_granting_controller() {
+        let (identity, controller, key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let grant = delegate(identity, controller, key);
+        let other = Pubkey::new_unique();
+
+        assert!(!grant.allows(&other, &controller, &key, delegate_permissions::UPDATE_METADATA, 0));
+        assert!(!grant.allows(&identity, &controller, &other, delegate_permissions::UPDATE_METADATA, 0));
+        // A new controller (recovery, transfer, guardian handover) silently voids old grants
+        assert!(!grant.allows(&identity, &other, &key, delegate_permissions::UPDATE_METADATA, 0));
+    }
 }
@@ -40,4 +40,13 @@ pub enum StakingError {
 
     #[msg("Cannot unstake more than staked amount")]
     ExcessiveUnstakeAmount,
+
+    #[msg("Stake is locked")]
+    StakeLocked,
+
+    #[msg("Stake is not locked by this authority")]
+    UnauthorizedUnlock,
+
+    #[msg("Stake account does not use the pre-lock layout")]
+    NotLegacyStakeAccount,
 }
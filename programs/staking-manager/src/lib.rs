@@ -107,6 +107,7 @@ pub mod staking_manager {
         let clock = Clock::get()?;
 
         require!(!pool.paused, StakingError::PoolPaused);
+        require!(!stake_account.is_locked(), StakingError::StakeLocked);
         require!(stake_account.unstake_requested_at == 0, StakingError::UnstakeAlreadyRequested);
         require!(amount <= stake_account.staked_amount, StakingError::ExcessiveUnstakeAmount);
         require!(amount > 0, StakingError::InsufficientStakedBalance);
@@ -269,12 +270,76 @@ pub mod staking_manager {
         Ok(())
     }
 
+    /// Grow a stake account created before stake locks existed to the current layout.
+    /// The appended `locked_by` is zero-filled, i.e. unlocked; the payer covers the extra rent.
+    pub fn migrate_stake_account(ctx: Context<MigrateStakeAccount>) -> Result<()> {
+        let stake_info = ctx.accounts.stake_account.to_account_info();
+        {
+            let data = stake_info.try_borrow_data()?;
+            require!(
+                data.len() == StakeAccount::LEGACY_LEN && &data[..8] == StakeAccount::DISCRIMINATOR,
+                StakingError::NotLegacyStakeAccount
+            );
+        }
+
+        let required = Rent::get()?.minimum_balance(StakeAccount::LEN);
+        let top_up = required.saturating_sub(stake_info.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: stake_info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        stake_info.resize(StakeAccount::LEN)?;
+
+        msg!("Stake account of {} migrated", ctx.accounts.owner.key());
+
+        Ok(())
+    }
+
+    /// Lock a stake against unstaking, signed by the owner and the locking program's
+    /// authority PDA (e.g. the verification oracle while the owner runs an active node)
+    pub fn lock_stake(ctx: Context<LockStake>) -> Result<()> {
+        let stake_account = &mut ctx.accounts.stake_account;
+
+        require!(!stake_account.is_locked(), StakingError::StakeLocked);
+        require!(stake_account.unstake_requested_at == 0, StakingError::UnstakeAlreadyRequested);
+
+        stake_account.locked_by = ctx.accounts.locker.key();
+
+        msg!("Stake of {} locked by {}", stake_account.owner, stake_account.locked_by);
+
+        Ok(())
+    }
+
+    /// Release a stake lock (locking authority only)
+    pub fn unlock_stake(ctx: Context<UnlockStake>) -> Result<()> {
+        let stake_account = &mut ctx.accounts.stake_account;
+
+        require!(
+            stake_account.locked_by == ctx.accounts.locker.key(),
+            StakingError::UnauthorizedUnlock
+        );
+        stake_account.locked_by = Pubkey::default();
+
+        msg!("Stake of {} unlocked", stake_account.owner);
+
+        Ok(())
+    }
+
     /// Update pool configuration (admin only)
     pub fn update_pool_config(
         ctx: Context<UpdatePoolConfig>,
         min_stake_amount: Option<u64>,
         reward_rate_bps: Option<u16>,
         unstake_cooldown: Option<i64>,
+        verification_oracle: Option<Pubkey>,
     ) -> Result<()> {
         let pool = &mut ctx.accounts.pool;
 
@@ -287,6 +352,9 @@ pub mod staking_manager {
         if let Some(cooldown) = unstake_cooldown {
             pool.unstake_cooldown = cooldown;
         }
+        if let Some(oracle) = verification_oracle {
+            pool.verification_oracle = oracle;
+        }
 
         msg!("Pool config updated");
 
@@ -526,20 +594,71 @@ pub struct SlashStaker<'info> {
 
     #[account(
         init,
-        payer = oracle,
+        payer = payer,
         space = SlashRecord::LEN,
         seeds = [b"slash", stake_account.owner.as_ref(), &stake_account.slash_count.to_le_bytes()],
         bump
     )]
     pub slash_record: Account<'info, SlashRecord>,
 
-    /// Verification oracle that initiates the slash
-    #[account(mut)]
+    /// Verification oracle authority that initiates the slash
     pub oracle: Signer<'info>,
 
+    /// Pays rent for the slash record
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct MigrateStakeAccount<'info> {
+    /// CHECK: Pre-lock StakeAccount, checked by size and discriminator in the handler
+    #[account(
+        mut,
+        seeds = [b"stake", owner.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub stake_account: AccountInfo<'info>,
+
+    /// CHECK: Stake owner, only used to derive the stake account address
+    pub owner: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct LockStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"stake", owner.key().as_ref()],
+        bump = stake_account.bump,
+        has_one = owner
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    pub owner: Signer<'info>,
+
+    /// Authority that will hold the lock
+    pub locker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnlockStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"stake", stake_account.owner.as_ref()],
+        bump = stake_account.bump
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    pub locker: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct UpdatePoolConfig<'info> {
     #[account(
@@ -6,7 +6,7 @@ pub struct StakingPool {
     pub admin: Pubkey,
     /// Identity registry program for CPI
     pub identity_registry: Pubkey,
-    /// Verification oracle program (can slash)
+    /// Verification oracle slashing authority (the oracle program's `oracle_authority` PDA)
     pub verification_oracle: Pubkey,
     /// Total SOL staked in the pool
     pub total_staked: u64,
@@ -63,6 +63,9 @@ pub struct StakeAccount {
     pub slash_count: u8,
     /// Bump seed
     pub bump: u8,
+    /// Program authority holding the stake locked against unstaking (default = unlocked).
+    /// Appended after `bump` so pre-lock accounts grow into it via migrate_stake_account.
+    pub locked_by: Pubkey,
 }
 
 impl StakeAccount {
@@ -76,7 +79,15 @@ impl StakeAccount {
         8 +  // unstake_amount
         8 +  // total_rewards_claimed
         1 +  // slash_count
-        1;   // bump
+        1 +  // bump
+        32;  // locked_by
+
+    /// Size of stake accounts created before `locked_by` was added
+    pub const LEGACY_LEN: usize = Self::LEN - 32;
+
+    pub fn is_locked(&self) -> bool {
+        self.locked_by != Pubkey::default()
+    }
 }
 
 #[account]
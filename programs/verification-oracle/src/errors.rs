@@ -47,6 +47,54 @@ pub enum OracleError {
     #[msg("Maximum oracles per request reached")]
     MaxOraclesReached,
 
+    #[msg("Identity has been deactivated")]
+    IdentityDeactivated,
+
+    #[msg("Identity is frozen")]
+    IdentityFrozen,
+
+    #[msg("Requester is neither the identity authority nor an authorized delegate")]
+    UnauthorizedRequester,
+
+    #[msg("Confirming an Aadhaar verification requires a nullifier")]
+    MissingNullifier,
+
+    #[msg("Nullifier does not match the one reported by other oracles")]
+    NullifierMismatch,
+
+    #[msg("Signer is not an oracle operator of the organization")]
+    UnauthorizedOperator,
+
+    #[msg("Only requests without responses can be cancelled")]
+    RequestNotCancellable,
+
+    #[msg("Oracle did not vote with the majority")]
+    NotInMajority,
+
+    #[msg("Oracle reward already claimed")]
+    RewardAlreadyClaimed,
+
+    #[msg("Request has not been finalized")]
+    RequestNotFinalized,
+
+    #[msg("Amount exceeds accrued protocol fees")]
+    InsufficientProtocolFees,
+
+    #[msg("Invalid basis points value")]
+    InvalidBps,
+
+    #[msg("Every response and its oracle node must be passed to finalize")]
+    IncompleteConsensusAccounts,
+
+    #[msg("Response or oracle node does not belong to this request")]
+    InvalidResponseAccount,
+
+    #[msg("Confirmations must attest an assurance level")]
+    MissingAssuranceLevel,
+
+    #[msg("Account does not use the pre-upgrade layout")]
+    NotLegacyAccount,
+
     #[msg("Arithmetic overflow")]
     Overflow,
 }
@@ -6,6 +6,13 @@ pub mod errors;
 
 use state::*;
 use errors::*;
+use identity_registry::state::{
+    delegate_permissions, org_roles, Delegate, IdentityAccount, IdentityStatus, OrgMember,
+};
+use staking_manager::{
+    program::StakingManager,
+    state::{StakeAccount, StakingPool},
+};
 
 declare_id!("35h6f6txjVcf8UshEaAm8fki2v1nhRLvRHFGNRwnTMrn");
 
@@ -36,6 +43,9 @@ pub mod verification_oracle {
         config.slash_percentage_bps = slash_percentage_bps;
         config.active_oracle_count = 0;
         config.total_verifications = 0;
+        config.protocol_fee_bps = 0;
+        config.protocol_fees_accrued = 0;
+        config.slash_supermajority_bps = OracleConfig::DEFAULT_SLASH_SUPERMAJORITY_BPS;
         config.bump = ctx.bumps.config;
 
         msg!("Oracle config initialized with {} required confirmations", required_confirmations);
@@ -49,9 +59,24 @@ pub mod verification_oracle {
         let oracle_node = &mut ctx.accounts.oracle_node;
         let clock = Clock::get()?;
 
-        // Verify the stake account has sufficient stake
-        // In production, this would verify via CPI to staking manager
-        // For now, we trust that the stake_account is valid
+        // The stake is read from the staking manager and must cover the network minimum
+        require!(
+            ctx.accounts.stake_account.staked_amount >= config.min_oracle_stake,
+            OracleError::InsufficientStake
+        );
+
+        // Nodes run for an organization must be registered by one of its oracle operators
+        if let Some(organization) = &ctx.accounts.organization {
+            require!(
+                organization.has_org_role(
+                    &organization.key(),
+                    &ctx.accounts.authority.key(),
+                    ctx.accounts.org_member.as_deref(),
+                    org_roles::ORACLE_OPERATOR,
+                ),
+                OracleError::UnauthorizedOperator
+            );
+        }
 
         oracle_node.authority = ctx.accounts.authority.key();
         oracle_node.stake_account = ctx.accounts.stake_account.key();
@@ -62,8 +87,22 @@ pub mod verification_oracle {
         oracle_node.slash_count = 0;
         oracle_node.registered_at = clock.unix_timestamp;
         oracle_node.last_active = clock.unix_timestamp;
+        oracle_node.organization = ctx.accounts.organization.as_ref().map(|o| o.key());
+        oracle_node.pending_slashes = 0;
         oracle_node.bump = ctx.bumps.oracle_node;
 
+        // Lock the stake so it cannot be withdrawn while the node is active
+        let seeds = &[b"oracle_authority".as_ref(), &[ctx.bumps.oracle_authority]];
+        staking_manager::cpi::lock_stake(CpiContext::new_with_signer(
+            ctx.accounts.staking_program.to_account_info(),
+            staking_manager::cpi::accounts::LockStake {
+                stake_account: ctx.accounts.stake_account.to_account_info(),
+                owner: ctx.accounts.authority.to_account_info(),
+                locker: ctx.accounts.oracle_authority.to_account_info(),
+            },
+            &[&seeds[..]],
+        ))?;
+
         // Update config
         let config = &mut ctx.accounts.config;
         config.active_oracle_count = config.active_oracle_count
@@ -80,22 +119,61 @@ pub mod verification_oracle {
         let oracle_node = &mut ctx.accounts.oracle_node;
         let config = &mut ctx.accounts.config;
 
+        require!(oracle_node.status == OracleStatus::Active, OracleError::OracleNotActive);
+
         oracle_node.status = OracleStatus::Inactive;
 
         config.active_oracle_count = config.active_oracle_count
             .checked_sub(1)
             .ok_or(OracleError::Overflow)?;
 
+        unlock_oracle_stake(
+            &ctx.accounts.stake_account,
+            &ctx.accounts.oracle_authority,
+            &ctx.accounts.staking_program,
+            ctx.bumps.oracle_authority,
+        )?;
+
         msg!("Oracle deregistered: {}", oracle_node.authority);
 
         Ok(())
     }
 
+    /// Deactivate an oracle whose stake has fallen below the network minimum
+    /// and release its lock (permissionless crank)
+    pub fn sync_oracle_stake(ctx: Context<SyncOracleStake>) -> Result<()> {
+        let oracle_node = &mut ctx.accounts.oracle_node;
+        let config = &mut ctx.accounts.config;
+
+        require!(oracle_node.status == OracleStatus::Active, OracleError::OracleNotActive);
+
+        if ctx.accounts.stake_account.staked_amount >= config.min_oracle_stake {
+            return Ok(());
+        }
+
+        oracle_node.status = OracleStatus::Inactive;
+        config.active_oracle_count = config.active_oracle_count
+            .checked_sub(1)
+            .ok_or(OracleError::Overflow)?;
+
+        unlock_oracle_stake(
+            &ctx.accounts.stake_account,
+            &ctx.accounts.oracle_authority,
+            &ctx.accounts.staking_program,
+            ctx.bumps.oracle_authority,
+        )?;
+
+        msg!("Oracle {} deactivated: stake below minimum", oracle_node.authority);
+
+        Ok(())
+    }
+
     /// Request a verification
     pub fn request_verification(
         ctx: Context<RequestVerification>,
         verification_type: u8,
         verification_hash: [u8; 32],
+        nonce: u64,
     ) -> Result<()> {
         let config = &ctx.accounts.config;
         let request = &mut ctx.accounts.verification_request;
@@ -103,6 +181,19 @@ pub mod verification_oracle {
 
         require!(verification_type < 64, OracleError::InvalidVerificationType);
 
+        // Requester must be the identity authority or a delegate allowed to request verification
+        let identity = &ctx.accounts.identity;
+        require!(
+            identity.is_authorized(
+                &identity.key(),
+                &ctx.accounts.requester.key(),
+                ctx.accounts.delegate.as_deref(),
+                delegate_permissions::REQUEST_VERIFICATION,
+                clock.unix_timestamp,
+            ),
+            OracleError::UnauthorizedRequester
+        );
+
         // Transfer verification fee from requester
         system_program::transfer(
             CpiContext::new(
@@ -127,6 +218,15 @@ pub mod verification_oracle {
         request.rejections = 0;
         request.responded_oracles = Vec::new();
         request.result = None;
+        request.nullifier = [0; 32];
+        request.requester = ctx.accounts.requester.key();
+        request.fee_refunded = 0;
+        request.protocol_fee = 0;
+        request.reward_per_oracle = 0;
+        request.fees_distributed = 0;
+        request.paid_oracles = Vec::new();
+        request.nonce = nonce;
+        request.assurance_level = 0;
         request.bump = ctx.bumps.verification_request;
 
         // Update config stats
@@ -146,14 +246,37 @@ pub mod verification_oracle {
         ctx: Context<SubmitVerification>,
         verified: bool,
         metadata_hash: [u8; 32],
+        nullifier: [u8; 32],
+        assurance_level: u8,
     ) -> Result<()> {
         let oracle_node = &mut ctx.accounts.oracle_node;
         let request = &mut ctx.accounts.verification_request;
         let response = &mut ctx.accounts.oracle_response;
         let clock = Clock::get()?;
 
-        // Verify oracle is active
+        // Verify oracle is active and still backed by the minimum stake
         require!(oracle_node.status == OracleStatus::Active, OracleError::OracleNotActive);
+        require!(
+            ctx.accounts.stake_account.staked_amount >= ctx.accounts.config.min_oracle_stake,
+            OracleError::InsufficientStake
+        );
+
+        // Organization nodes only respond while the signer is still an oracle operator
+        if let Some(organization_key) = oracle_node.organization {
+            let organization = ctx.accounts.organization
+                .as_ref()
+                .filter(|o| o.key() == organization_key)
+                .ok_or(OracleError::UnauthorizedOperator)?;
+            require!(
+                organization.has_org_role(
+                    &organization_key,
+                    &oracle_node.authority,
+                    ctx.accounts.org_member.as_deref(),
+                    org_roles::ORACLE_OPERATOR,
+                ),
+                OracleError::UnauthorizedOperator
+            );
+        }
 
         // Verify request is still pending/in progress
         require!(
@@ -177,16 +300,35 @@ pub mod verification_oracle {
             OracleError::MaxOraclesReached
         );
 
+        require!(!verified || assurance_level > 0, OracleError::MissingAssuranceLevel);
+
+        // Confirming oracles must agree on the Aadhaar nullifier
+        if verified && request.verification_type == verification_types::AADHAAR {
+            require!(nullifier != [0; 32], OracleError::MissingNullifier);
+            if request.nullifier == [0; 32] {
+                request.nullifier = nullifier;
+            } else {
+                require!(request.nullifier == nullifier, OracleError::NullifierMismatch);
+            }
+        }
+
         // Record the response
         response.request = request.key();
         response.oracle = oracle_node.authority;
         response.verified = verified;
         response.responded_at = clock.unix_timestamp;
         response.metadata_hash = metadata_hash;
+        response.assurance_level = if verified { assurance_level } else { 0 };
         response.bump = ctx.bumps.oracle_response;
 
         // Update request
         if verified {
+            // A verification is only as strong as the weakest check that confirmed it
+            request.assurance_level = if request.confirmations == 0 {
+                assurance_level
+            } else {
+                request.assurance_level.min(assurance_level)
+            };
             request.confirmations = request.confirmations
                 .checked_add(1)
                 .ok_or(OracleError::Overflow)?;
@@ -213,7 +355,9 @@ pub mod verification_oracle {
     }
 
     /// Finalize verification after enough confirmations
-    pub fn finalize_verification(ctx: Context<FinalizeVerification>) -> Result<()> {
+    pub fn finalize_verification<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FinalizeVerification<'info>>,
+    ) -> Result<()> {
         let config = &ctx.accounts.config;
         let request = &mut ctx.accounts.verification_request;
 
@@ -234,6 +378,61 @@ pub mod verification_oracle {
         let verified = request.confirmations > request.rejections;
         request.result = Some(verified);
 
+        let majority = if verified { request.confirmations } else { request.rejections };
+
+        // Walk every (response, oracle node) pair to record agreement with the consensus.
+        // Dissenters are only queued for slashing when the consensus reached the configured
+        // supermajority, so honest minority votes on close cases go unpunished.
+        require!(
+            ctx.remaining_accounts.len() == total_responses as usize * 2,
+            OracleError::IncompleteConsensusAccounts
+        );
+        let supermajority = majority as u32 * 10_000
+            >= total_responses as u32 * config.slash_supermajority_bps as u32;
+        let mut counted: Vec<Pubkey> = Vec::with_capacity(total_responses as usize);
+
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let response: Account<OracleResponse> = Account::try_from(&pair[0])?;
+            let mut node: Account<OracleNode> = Account::try_from(&pair[1])?;
+
+            require_keys_eq!(response.request, request.key(), OracleError::InvalidResponseAccount);
+            require_keys_eq!(node.authority, response.oracle, OracleError::InvalidResponseAccount);
+            require!(pair[1].is_writable, OracleError::InvalidResponseAccount);
+            require!(!counted.contains(&response.oracle), OracleError::InvalidResponseAccount);
+            counted.push(response.oracle);
+
+            if response.verified == verified {
+                node.successful_verifications = node.successful_verifications
+                    .checked_add(1)
+                    .ok_or(OracleError::Overflow)?;
+            } else {
+                node.failed_verifications = node.failed_verifications
+                    .checked_add(1)
+                    .ok_or(OracleError::Overflow)?;
+                if supermajority {
+                    node.pending_slashes = node.pending_slashes
+                        .checked_add(1)
+                        .ok_or(OracleError::Overflow)?;
+                    msg!("Slash queued for oracle {}", node.authority);
+                }
+            }
+
+            node.exit(&crate::ID)?;
+        }
+
+        // Take the protocol cut and split the rest among the majority; dust goes to the protocol
+        let protocol_fee = (request.fee_paid as u128 * config.protocol_fee_bps as u128 / 10_000) as u64;
+        let oracle_pool = request.fee_paid - protocol_fee;
+        let reward_per_oracle = if majority > 0 { oracle_pool / majority as u64 } else { 0 };
+        request.reward_per_oracle = reward_per_oracle;
+        request.protocol_fee = request.fee_paid - reward_per_oracle * majority as u64;
+
+        let config = &mut ctx.accounts.config;
+        config.protocol_fees_accrued = config.protocol_fees_accrued
+            .checked_add(request.protocol_fee)
+            .ok_or(OracleError::Overflow)?;
+        let config_bump = config.bump;
+
         if verified {
             request.status = VerificationStatus::Verified;
 
@@ -243,15 +442,22 @@ pub mod verification_oracle {
                 identity_account: ctx.accounts.identity.to_account_info(),
                 oracle: ctx.accounts.oracle_signer.to_account_info(),
                 config: ctx.accounts.identity_config.to_account_info(),
+                verification_record: ctx.accounts.verification_record.to_account_info(),
+                source_request: request.to_account_info(),
+                aadhaar_nullifier: ctx.accounts.aadhaar_nullifier.as_ref().map(|a| a.to_account_info()),
+                payer: ctx.accounts.finalizer.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
             };
 
-            let seeds = &[b"config".as_ref(), &[config.bump]];
+            let seeds = &[b"config".as_ref(), &[config_bump]];
             let signer_seeds = &[&seeds[..]];
 
             identity_registry::cpi::update_verification_status(
                 CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds),
                 request.verification_type,
                 true,
+                request.assurance_level,
+                request.nullifier,
             )?;
 
             msg!("Verification finalized: VERIFIED");
@@ -279,14 +485,109 @@ pub mod verification_oracle {
         request.status = VerificationStatus::Expired;
         request.result = None;
 
-        // TODO: Refund fee to requester
+        refund_requester(
+            request,
+            &ctx.accounts.fee_vault,
+            &ctx.accounts.requester,
+            &ctx.accounts.system_program,
+            ctx.bumps.fee_vault,
+        )?;
 
-        msg!("Verification request expired");
+        msg!("Verification request expired, {} lamports refunded", request.fee_refunded);
 
         Ok(())
     }
 
-    /// Slash an oracle for misbehavior
+    /// Cancel a request no oracle has responded to yet and refund the fee (requester only)
+    pub fn cancel_verification(ctx: Context<CancelVerification>) -> Result<()> {
+        let request = &mut ctx.accounts.verification_request;
+
+        require!(
+            request.status == VerificationStatus::Pending,
+            OracleError::RequestNotCancellable
+        );
+
+        request.status = VerificationStatus::Cancelled;
+        request.result = None;
+
+        refund_requester(
+            request,
+            &ctx.accounts.fee_vault,
+            &ctx.accounts.requester,
+            &ctx.accounts.system_program,
+            ctx.bumps.fee_vault,
+        )?;
+
+        msg!("Verification request cancelled, {} lamports refunded", request.fee_refunded);
+
+        Ok(())
+    }
+
+    /// Pay a majority oracle its share of a finalized request's fee
+    pub fn claim_oracle_reward(ctx: Context<ClaimOracleReward>) -> Result<()> {
+        let request = &mut ctx.accounts.verification_request;
+        let response = &ctx.accounts.oracle_response;
+        let oracle = ctx.accounts.authority.key();
+
+        let result = request.result.ok_or(OracleError::RequestNotFinalized)?;
+        require!(
+            request.status == VerificationStatus::Verified ||
+            request.status == VerificationStatus::Rejected,
+            OracleError::RequestNotFinalized
+        );
+        require!(response.verified == result, OracleError::NotInMajority);
+        require!(!request.paid_oracles.contains(&oracle), OracleError::RewardAlreadyClaimed);
+
+        let reward = request.reward_per_oracle;
+        pay_from_vault(
+            &ctx.accounts.fee_vault,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program,
+            reward,
+            ctx.bumps.fee_vault,
+        )?;
+
+        request.paid_oracles.push(oracle);
+        request.fees_distributed = request.fees_distributed
+            .checked_add(reward)
+            .ok_or(OracleError::Overflow)?;
+
+        msg!("Oracle {} claimed {} lamports", oracle, reward);
+
+        Ok(())
+    }
+
+    /// Withdraw accrued protocol fees from the fee vault (admin only)
+    pub fn withdraw_protocol_fees(ctx: Context<WithdrawProtocolFees>, amount: u64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(amount <= config.protocol_fees_accrued, OracleError::InsufficientProtocolFees);
+
+        pay_from_vault(
+            &ctx.accounts.fee_vault,
+            &ctx.accounts.recipient,
+            &ctx.accounts.system_program,
+            amount,
+            ctx.bumps.fee_vault,
+        )?;
+        config.protocol_fees_accrued -= amount;
+
+        msg!("Withdrew {} lamports of protocol fees to {}", amount, ctx.accounts.recipient.key());
+
+        Ok(())
+    }
+
+    /// Set the protocol cut of verification fees (admin only)
+    pub fn set_protocol_fee(ctx: Context<UpdateConfig>, protocol_fee_bps: u16) -> Result<()> {
+        require!(protocol_fee_bps <= 10_000, OracleError::InvalidBps);
+        ctx.accounts.config.protocol_fee_bps = protocol_fee_bps;
+
+        msg!("Protocol fee set to {} bps", protocol_fee_bps);
+
+        Ok(())
+    }
+
+    /// Slash an oracle for misbehavior. The admin may slash for any reason; anyone may
+    /// execute a consensus-violation slash queued at finalization.
     pub fn slash_oracle(
         ctx: Context<SlashOracle>,
         reason: staking_manager::state::SlashReason,
@@ -294,28 +595,77 @@ pub mod verification_oracle {
         let config = &ctx.accounts.config;
         let oracle_node = &mut ctx.accounts.oracle_node;
 
-        // Calculate slash amount
-        // This would typically be calculated based on their stake
-        // For now, we just mark them and CPI to staking manager
+        let queued = reason == staking_manager::state::SlashReason::ConsensusViolation
+            && oracle_node.pending_slashes > 0;
+        require!(
+            queued || ctx.accounts.authority.key() == config.admin,
+            OracleError::UnauthorizedAdmin
+        );
+        if queued {
+            oracle_node.pending_slashes -= 1;
+        }
+
+        // Slash a fixed share of the current stake
+        let amount = (ctx.accounts.stake_account.staked_amount as u128
+            * config.slash_percentage_bps as u128
+            / 10_000) as u64;
+
+        // The staking manager rejects empty slashes, so a share that rounds down to zero
+        // (tiny stake or a 0 bps penalty) still counts against the node without the CPI
+        if amount > 0 {
+            // The staking pool recognizes the oracle authority PDA as its slasher
+            let seeds = &[b"oracle_authority".as_ref(), &[ctx.bumps.oracle_authority]];
+            staking_manager::cpi::slash(
+                CpiContext::new_with_signer(
+                    ctx.accounts.staking_program.to_account_info(),
+                    staking_manager::cpi::accounts::SlashStaker {
+                        pool: ctx.accounts.staking_pool.to_account_info(),
+                        stake_account: ctx.accounts.stake_account.to_account_info(),
+                        slash_record: ctx.accounts.slash_record.to_account_info(),
+                        oracle: ctx.accounts.oracle_authority.to_account_info(),
+                        payer: ctx.accounts.authority.to_account_info(),
+                        system_program: ctx.accounts.system_program.to_account_info(),
+                    },
+                    &[&seeds[..]],
+                ),
+                amount,
+                reason,
+            )?;
+            ctx.accounts.stake_account.reload()?;
+        }
 
         oracle_node.slash_count = oracle_node.slash_count
             .checked_add(1)
             .ok_or(OracleError::Overflow)?;
-        oracle_node.failed_verifications = oracle_node.failed_verifications
-            .checked_add(1)
-            .ok_or(OracleError::Overflow)?;
 
-        // If slashed too many times, deactivate
-        if oracle_node.slash_count >= 3 {
-            oracle_node.status = OracleStatus::Slashed;
+        // Deactivate after too many slashes, or once the remaining stake is below the minimum
+        if oracle_node.status == OracleStatus::Active {
+            let next_status = if oracle_node.slash_count >= 3 {
+                Some(OracleStatus::Slashed)
+            } else if ctx.accounts.stake_account.staked_amount < config.min_oracle_stake {
+                Some(OracleStatus::Inactive)
+            } else {
+                None
+            };
 
-            let config_mut = &mut ctx.accounts.config;
-            config_mut.active_oracle_count = config_mut.active_oracle_count
-                .checked_sub(1)
-                .ok_or(OracleError::Overflow)?;
+            if let Some(status) = next_status {
+                oracle_node.status = status;
+
+                let config_mut = &mut ctx.accounts.config;
+                config_mut.active_oracle_count = config_mut.active_oracle_count
+                    .checked_sub(1)
+                    .ok_or(OracleError::Overflow)?;
+
+                unlock_oracle_stake(
+                    &ctx.accounts.stake_account,
+                    &ctx.accounts.oracle_authority,
+                    &ctx.accounts.staking_program,
+                    ctx.bumps.oracle_authority,
+                )?;
+            }
         }
 
-        msg!("Oracle {} slashed for {:?}", oracle_node.authority, reason);
+        msg!("Oracle {} slashed {} lamports for {:?}", oracle_node.authority, amount, reason);
 
         Ok(())
     }
@@ -328,6 +678,7 @@ pub mod verification_oracle {
         required_confirmations: Option<u8>,
         verification_timeout: Option<i64>,
         slash_percentage_bps: Option<u16>,
+        slash_supermajority_bps: Option<u16>,
     ) -> Result<()> {
         let config = &mut ctx.accounts.config;
 
@@ -346,15 +697,216 @@ pub mod verification_oracle {
         if let Some(v) = slash_percentage_bps {
             config.slash_percentage_bps = v;
         }
+        if let Some(v) = slash_supermajority_bps {
+            // A bare majority is never enough to penalize the minority
+            require!(v > 5_000 && v <= 10_000, OracleError::InvalidBps);
+            config.slash_supermajority_bps = v;
+        }
 
         msg!("Oracle config updated");
 
         Ok(())
     }
+
+    /// Grow a config created before protocol fees and consensus slashing to the current layout.
+    /// The protocol fee starts at zero and the slash supermajority at its default.
+    pub fn migrate_oracle_config(ctx: Context<MigrateOracleConfig>) -> Result<()> {
+        let config_info = ctx.accounts.config.to_account_info();
+        grow_legacy_account(
+            &config_info,
+            OracleConfig::LEGACY_LEN,
+            OracleConfig::LEN,
+            OracleConfig::DISCRIMINATOR,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )?;
+
+        {
+            let mut data = config_info.try_borrow_mut_data()?;
+            let mut config = OracleConfig::try_deserialize(&mut &data[..])?;
+            config.slash_supermajority_bps = OracleConfig::DEFAULT_SLASH_SUPERMAJORITY_BPS;
+            config.try_serialize(&mut &mut data[..])?;
+        }
+
+        msg!("Oracle config migrated");
+
+        Ok(())
+    }
+
+    /// Grow an oracle node registered before organizations and queued slashes to the
+    /// current layout. The appended fields are zero-filled: no organization, nothing queued.
+    pub fn migrate_oracle_node(ctx: Context<MigrateOracleNode>) -> Result<()> {
+        grow_legacy_account(
+            &ctx.accounts.oracle_node.to_account_info(),
+            OracleNode::LEGACY_LEN,
+            OracleNode::LEN,
+            OracleNode::DISCRIMINATOR,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )?;
+
+        msg!("Oracle node of {} migrated", ctx.accounts.authority.key());
+
+        Ok(())
+    }
+
+    /// Grow a request created before fee accounting to the current layout (admin only).
+    /// Older requests did not store who paid the fee, so the admin backfills the requester
+    /// from the creating transaction; confirmations already received carried no assurance
+    /// level and are recorded at the lowest one.
+    pub fn migrate_verification_request(
+        ctx: Context<MigrateVerificationRequest>,
+        requester: Pubkey,
+    ) -> Result<()> {
+        let request_info = ctx.accounts.verification_request.to_account_info();
+        grow_legacy_account(
+            &request_info,
+            VerificationRequest::LEGACY_LEN,
+            VerificationRequest::LEN,
+            VerificationRequest::DISCRIMINATOR,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+        )?;
+
+        {
+            let mut data = request_info.try_borrow_mut_data()?;
+            let mut request = VerificationRequest::try_deserialize(&mut &data[..])?;
+            request.requester = requester;
+            if request.confirmations > 0 {
+                request.assurance_level = 1;
+            }
+            request.try_serialize(&mut &mut data[..])?;
+        }
+
+        msg!("Verification request {} migrated", request_info.key());
+
+        Ok(())
+    }
+
+    /// Grow a response submitted before assurance levels to the current layout.
+    /// A confirmation is recorded at the lowest assurance level, a rejection at zero.
+    pub fn migrate_oracle_response(ctx: Context<MigrateOracleResponse>) -> Result<()> {
+        let response_info = ctx.accounts.oracle_response.to_account_info();
+        grow_legacy_account(
+            &response_info,
+            OracleResponse::LEGACY_LEN,
+            OracleResponse::LEN,
+            OracleResponse::DISCRIMINATOR,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )?;
+
+        {
+            let mut data = response_info.try_borrow_mut_data()?;
+            let mut response = OracleResponse::try_deserialize(&mut &data[..])?;
+            if response.verified {
+                response.assurance_level = 1;
+            }
+            response.try_serialize(&mut &mut data[..])?;
+        }
+
+        msg!("Oracle response {} migrated", response_info.key());
+
+        Ok(())
+    }
 }
 
 // ============== Account Contexts ==============
 
+/// Resize an account written with a pre-upgrade layout to `len`, topping up its rent
+/// from `payer`. The bytes appended past the old layout are zero-filled.
+fn grow_legacy_account<'info>(
+    account: &AccountInfo<'info>,
+    legacy_len: usize,
+    len: usize,
+    discriminator: &[u8],
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    {
+        let data = account.try_borrow_data()?;
+        require!(
+            data.len() == legacy_len && &data[..8] == discriminator,
+            OracleError::NotLegacyAccount
+        );
+    }
+
+    let required = Rent::get()?.minimum_balance(len);
+    let top_up = required.saturating_sub(account.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    account.resize(len)?;
+
+    Ok(())
+}
+
+/// Transfer lamports out of the fee vault PDA
+fn pay_from_vault<'info>(
+    fee_vault: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    amount: u64,
+    fee_vault_bump: u8,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let seeds = &[b"fee_vault".as_ref(), &[fee_vault_bump]];
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: fee_vault.clone(),
+                to: to.clone(),
+            },
+            &[&seeds[..]],
+        ),
+        amount,
+    )
+}
+
+/// Release the oracle program's lock on a node's stake
+fn unlock_oracle_stake<'info>(
+    stake_account: &Account<'info, StakeAccount>,
+    oracle_authority: &AccountInfo<'info>,
+    staking_program: &Program<'info, StakingManager>,
+    oracle_authority_bump: u8,
+) -> Result<()> {
+    let seeds = &[b"oracle_authority".as_ref(), &[oracle_authority_bump]];
+    staking_manager::cpi::unlock_stake(CpiContext::new_with_signer(
+        staking_program.to_account_info(),
+        staking_manager::cpi::accounts::UnlockStake {
+            stake_account: stake_account.to_account_info(),
+            locker: oracle_authority.clone(),
+        },
+        &[&seeds[..]],
+    ))
+}
+
+/// Return the unspent fee of an unfinalized request to its requester
+fn refund_requester<'info>(
+    request: &mut VerificationRequest,
+    fee_vault: &AccountInfo<'info>,
+    requester: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    fee_vault_bump: u8,
+) -> Result<()> {
+    let refund = request.fee_paid - request.fee_refunded;
+    pay_from_vault(fee_vault, requester, system_program, refund, fee_vault_bump)?;
+    request.fee_refunded = request.fee_paid;
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(
@@ -390,12 +942,29 @@ pub struct RegisterOracle<'info> {
     )]
     pub oracle_node: Account<'info, OracleNode>,
 
-    /// CHECK: Stake account in staking manager (verified via constraints in production)
-    pub stake_account: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"stake", authority.key().as_ref()],
+        seeds::program = staking_program.key(),
+        bump = stake_account.bump
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    /// CHECK: Program authority PDA that holds oracle stake locks
+    #[account(seeds = [b"oracle_authority"], bump)]
+    pub oracle_authority: AccountInfo<'info>,
+
+    /// Organization identity the node operates for, if any
+    pub organization: Option<Account<'info, IdentityAccount>>,
+
+    /// Signer's roster entry in `organization`
+    pub org_member: Option<Account<'info, OrgMember>>,
 
     #[account(mut)]
     pub authority: Signer<'info>,
 
+    pub staking_program: Program<'info, StakingManager>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -412,15 +981,52 @@ pub struct DeregisterOracle<'info> {
         mut,
         seeds = [b"oracle", authority.key().as_ref()],
         bump = oracle_node.bump,
-        has_one = authority
+        has_one = authority,
+        has_one = stake_account
     )]
     pub oracle_node: Account<'info, OracleNode>,
 
+    #[account(mut)]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    /// CHECK: Program authority PDA that holds oracle stake locks
+    #[account(seeds = [b"oracle_authority"], bump)]
+    pub oracle_authority: AccountInfo<'info>,
+
     pub authority: Signer<'info>,
+
+    pub staking_program: Program<'info, StakingManager>,
 }
 
 #[derive(Accounts)]
-#[instruction(verification_type: u8, verification_hash: [u8; 32])]
+pub struct SyncOracleStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, OracleConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"oracle", oracle_node.authority.as_ref()],
+        bump = oracle_node.bump,
+        has_one = stake_account
+    )]
+    pub oracle_node: Account<'info, OracleNode>,
+
+    #[account(mut)]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    /// CHECK: Program authority PDA that holds oracle stake locks
+    #[account(seeds = [b"oracle_authority"], bump)]
+    pub oracle_authority: AccountInfo<'info>,
+
+    pub staking_program: Program<'info, StakingManager>,
+}
+
+#[derive(Accounts)]
+#[instruction(verification_type: u8, verification_hash: [u8; 32], nonce: u64)]
 pub struct RequestVerification<'info> {
     #[account(
         mut,
@@ -433,13 +1039,17 @@ pub struct RequestVerification<'info> {
         init,
         payer = requester,
         space = VerificationRequest::LEN,
-        seeds = [b"request", identity.key().as_ref(), &[verification_type]],
+        seeds = [b"request", identity.key().as_ref(), &[verification_type], &nonce.to_le_bytes()],
         bump
     )]
     pub verification_request: Account<'info, VerificationRequest>,
 
-    /// CHECK: Identity account from identity registry
-    pub identity: AccountInfo<'info>,
+    /// Identity account from identity registry
+    #[account(
+        constraint = identity.status != IdentityStatus::Deactivated @ OracleError::IdentityDeactivated,
+        constraint = identity.status != IdentityStatus::Frozen @ OracleError::IdentityFrozen
+    )]
+    pub identity: Account<'info, IdentityAccount>,
 
     /// CHECK: Fee vault to receive verification fees
     #[account(
@@ -449,9 +1059,12 @@ pub struct RequestVerification<'info> {
     )]
     pub fee_vault: AccountInfo<'info>,
 
+    /// Identity authority, or a delegate holding REQUEST_VERIFICATION
     #[account(mut)]
     pub requester: Signer<'info>,
 
+    pub delegate: Option<Account<'info, Delegate>>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -464,10 +1077,13 @@ pub struct SubmitVerification<'info> {
         mut,
         seeds = [b"oracle", authority.key().as_ref()],
         bump = oracle_node.bump,
-        has_one = authority
+        has_one = authority,
+        has_one = stake_account
     )]
     pub oracle_node: Account<'info, OracleNode>,
 
+    pub stake_account: Account<'info, StakeAccount>,
+
     #[account(mut)]
     pub verification_request: Account<'info, VerificationRequest>,
 
@@ -480,6 +1096,11 @@ pub struct SubmitVerification<'info> {
     )]
     pub oracle_response: Account<'info, OracleResponse>,
 
+    /// Required for organization-operated nodes
+    pub organization: Option<Account<'info, IdentityAccount>>,
+
+    pub org_member: Option<Account<'info, OrgMember>>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
@@ -488,14 +1109,14 @@ pub struct SubmitVerification<'info> {
 
 #[derive(Accounts)]
 pub struct FinalizeVerification<'info> {
-    #[account(seeds = [b"config"], bump = config.bump)]
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
     pub config: Account<'info, OracleConfig>,
 
     #[account(mut)]
     pub verification_request: Account<'info, VerificationRequest>,
 
-    /// CHECK: Identity account to update
-    #[account(mut)]
+    /// CHECK: Identity account to update, must be the one the request was made for
+    #[account(mut, address = verification_request.identity)]
     pub identity: AccountInfo<'info>,
 
     /// CHECK: Identity registry config
@@ -504,10 +1125,22 @@ pub struct FinalizeVerification<'info> {
     /// CHECK: Oracle signer (this program as PDA)
     pub oracle_signer: AccountInfo<'info>,
 
+    /// CHECK: Verification record in identity registry, created on first verification
+    #[account(mut)]
+    pub verification_record: AccountInfo<'info>,
+
+    /// CHECK: Aadhaar nullifier in identity registry, required for Aadhaar requests
+    #[account(mut)]
+    pub aadhaar_nullifier: Option<AccountInfo<'info>>,
+
     /// CHECK: Identity registry program for CPI
     pub identity_registry_program: AccountInfo<'info>,
 
+    /// Pays for the verification record and nullifier if they do not exist yet
+    #[account(mut)]
     pub finalizer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -515,11 +1148,58 @@ pub struct ExpireVerification<'info> {
     #[account(mut)]
     pub verification_request: Account<'info, VerificationRequest>,
 
+    /// CHECK: Fee vault holding the request fee
+    #[account(mut, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: AccountInfo<'info>,
+
+    /// CHECK: Original requester, receives the refund
+    #[account(mut, address = verification_request.requester)]
+    pub requester: AccountInfo<'info>,
+
     pub anyone: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct SlashOracle<'info> {
+pub struct CancelVerification<'info> {
+    #[account(mut, has_one = requester)]
+    pub verification_request: Account<'info, VerificationRequest>,
+
+    /// CHECK: Fee vault holding the request fee
+    #[account(mut, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub requester: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimOracleReward<'info> {
+    #[account(mut)]
+    pub verification_request: Account<'info, VerificationRequest>,
+
+    #[account(
+        seeds = [b"response", verification_request.key().as_ref(), authority.key().as_ref()],
+        bump = oracle_response.bump
+    )]
+    pub oracle_response: Account<'info, OracleResponse>,
+
+    /// CHECK: Fee vault paying the reward
+    #[account(mut, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: AccountInfo<'info>,
+
+    /// Oracle that submitted the response, receives the reward
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawProtocolFees<'info> {
     #[account(
         mut,
         seeds = [b"config"],
@@ -528,14 +1208,62 @@ pub struct SlashOracle<'info> {
     )]
     pub config: Account<'info, OracleConfig>,
 
+    /// CHECK: Fee vault holding protocol fees
+    #[account(mut, seeds = [b"fee_vault"], bump)]
+    pub fee_vault: AccountInfo<'info>,
+
+    /// CHECK: Treasury account receiving the withdrawal
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SlashOracle<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, OracleConfig>,
+
     #[account(
         mut,
         seeds = [b"oracle", oracle_node.authority.as_ref()],
-        bump = oracle_node.bump
+        bump = oracle_node.bump,
+        has_one = stake_account
     )]
     pub oracle_node: Account<'info, OracleNode>,
 
-    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"pool"],
+        seeds::program = staking_program.key(),
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    /// CHECK: Slash record created by the staking manager (untouched when the slash rounds to zero)
+    #[account(mut)]
+    pub slash_record: UncheckedAccount<'info>,
+
+    /// CHECK: Program authority PDA that the staking pool recognizes as its slasher
+    #[account(seeds = [b"oracle_authority"], bump)]
+    pub oracle_authority: AccountInfo<'info>,
+
+    /// Config admin, or anyone executing a queued consensus-violation slash; pays for the slash record
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub staking_program: Program<'info, StakingManager>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -550,3 +1278,71 @@ pub struct UpdateConfig<'info> {
 
     pub admin: Signer<'info>,
 }
+
+#[derive(Accounts)]
+pub struct MigrateOracleConfig<'info> {
+    /// CHECK: Pre-upgrade OracleConfig, checked by size and discriminator in the handler
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        owner = crate::ID
+    )]
+    pub config: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateOracleNode<'info> {
+    /// CHECK: Pre-upgrade OracleNode, checked by size and discriminator in the handler
+    #[account(
+        mut,
+        seeds = [b"oracle", authority.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub oracle_node: AccountInfo<'info>,
+
+    /// CHECK: Oracle authority, only used to derive the node address
+    pub authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateVerificationRequest<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin
+    )]
+    pub config: Account<'info, OracleConfig>,
+
+    /// CHECK: Pre-upgrade VerificationRequest, checked by size and discriminator in the handler
+    #[account(mut, owner = crate::ID)]
+    pub verification_request: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateOracleResponse<'info> {
+    /// CHECK: Pre-upgrade OracleResponse, checked by size and discriminator in the handler
+    #[account(mut, owner = crate::ID)]
+    pub oracle_response: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
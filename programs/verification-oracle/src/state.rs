@@ -25,6 +25,14 @@ pub struct OracleConfig {
     pub total_verifications: u64,
     /// Bump seed
     pub bump: u8,
+    /// Protocol cut of each finalized request's fee (basis points).
+    /// This and the fields below are appended after `bump` so older configs grow into
+    /// them via migrate_oracle_config.
+    pub protocol_fee_bps: u16,
+    /// Protocol fees held in the fee vault and not yet withdrawn
+    pub protocol_fees_accrued: u64,
+    /// Share of responses the consensus must hold before dissenters are queued for slashing (basis points)
+    pub slash_supermajority_bps: u16,
 }
 
 impl OracleConfig {
@@ -39,7 +47,16 @@ impl OracleConfig {
         2 +  // slash_percentage_bps
         4 +  // active_oracle_count
         8 +  // total_verifications
-        1;   // bump
+        1 +  // bump
+        2 +  // protocol_fee_bps
+        8 +  // protocol_fees_accrued
+        2;   // slash_supermajority_bps
+
+    /// Size of configs created before protocol fees and the slash supermajority were added
+    pub const LEGACY_LEN: usize = Self::LEN - 12;
+
+    /// Only dissent from at least an 80% consensus is penalized by default
+    pub const DEFAULT_SLASH_SUPERMAJORITY_BPS: u16 = 8_000;
 }
 
 /// Registered oracle node
@@ -65,6 +82,12 @@ pub struct OracleNode {
     pub last_active: i64,
     /// Bump seed
     pub bump: u8,
+    /// Organization identity operating this node, if any.
+    /// This and `pending_slashes` are appended after `bump` so older nodes grow into
+    /// them via migrate_oracle_node.
+    pub organization: Option<Pubkey>,
+    /// Consensus-violation slashes queued at finalization and not yet executed
+    pub pending_slashes: u8,
 }
 
 impl OracleNode {
@@ -78,7 +101,12 @@ impl OracleNode {
         1 +  // slash_count
         8 +  // registered_at
         8 +  // last_active
-        1;   // bump
+        1 +  // bump
+        33 + // organization
+        1;   // pending_slashes
+
+    /// Size of nodes registered before organizations and queued slashes were added
+    pub const LEGACY_LEN: usize = Self::LEN - 34;
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
@@ -121,6 +149,26 @@ pub struct VerificationRequest {
     pub result: Option<bool>,
     /// Bump seed
     pub bump: u8,
+    /// Salted hash of the Aadhaar number reported by confirming oracles (Aadhaar requests only).
+    /// This and the fields below are appended after `bump` so older requests grow into
+    /// them via migrate_verification_request.
+    pub nullifier: [u8; 32],
+    /// Wallet that paid the fee and receives any refund
+    pub requester: Pubkey,
+    /// Fee returned to the requester on expiry or cancellation
+    pub fee_refunded: u64,
+    /// Protocol cut taken at finalization
+    pub protocol_fee: u64,
+    /// Reward each majority oracle can claim after finalization
+    pub reward_per_oracle: u64,
+    /// Total rewards paid out to oracles so far
+    pub fees_distributed: u64,
+    /// Oracles that have claimed their reward
+    pub paid_oracles: Vec<Pubkey>,
+    /// Requester-chosen nonce so an identity can re-request a verification type
+    pub nonce: u64,
+    /// Lowest assurance level attested by a confirming oracle
+    pub assurance_level: u8,
 }
 
 impl VerificationRequest {
@@ -137,7 +185,19 @@ impl VerificationRequest {
         1 +  // rejections
         4 + (32 * Self::MAX_ORACLES) + // responded_oracles (vec)
         2 +  // result (Option<bool>)
-        1;   // bump
+        1 +  // bump
+        32 + // nullifier
+        32 + // requester
+        8 +  // fee_refunded
+        8 +  // protocol_fee
+        8 +  // reward_per_oracle
+        8 +  // fees_distributed
+        4 + (32 * Self::MAX_ORACLES) + // paid_oracles (vec)
+        8 +  // nonce
+        1;   // assurance_level
+
+    /// Size of requests created before nullifiers, fee accounting and nonces were added
+    pub const LEGACY_LEN: usize = Self::LEN - 32 - 32 - 8 * 4 - (4 + 32 * Self::MAX_ORACLES) - 8 - 1;
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
@@ -147,6 +207,7 @@ pub enum VerificationStatus {
     Verified,
     Rejected,
     Expired,
+    Cancelled,
 }
 
 impl Default for VerificationStatus {
@@ -170,6 +231,9 @@ pub struct OracleResponse {
     pub metadata_hash: [u8; 32],
     /// Bump seed
     pub bump: u8,
+    /// Assurance level of the check the oracle performed (0 for rejections).
+    /// Appended after `bump` so older responses grow into it via migrate_oracle_response.
+    pub assurance_level: u8,
 }
 
 impl OracleResponse {
@@ -179,7 +243,11 @@ impl OracleResponse {
         1 +  // verified
         8 +  // responded_at
         32 + // metadata_hash
-        1;   // bump
+        1 +  // bump
+        1;   // assurance_level
+
+    /// Size of responses submitted before assurance levels were added
+    pub const LEGACY_LEN: usize = Self::LEN - 1;
 }
 
 /// Verification type constants matching identity registry bitmap
@@ -1,15 +1,27 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { CredentialManager } from "../target/types/credential_manager";
+import { IdentityRegistry } from "../target/types/identity_registry";
+import { VerificationOracle } from "../target/types/verification_oracle";
 import { expect } from "chai";
 import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
 import * as crypto from "crypto";
+import {
+  CREDENTIAL_VERIFICATION_SCOPE,
+  createIdentity,
+  findIdentityConfigPDA,
+  generateHash,
+  initializeRegistryConfig,
+  REGISTRY_CONFIG,
+} from "./utils/test-helpers";
 
 describe("credential-manager", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
   const program = anchor.workspace.CredentialManager as Program<CredentialManager>;
+  const registryProgram = anchor.workspace.IdentityRegistry as Program<IdentityRegistry>;
+  const oracleProgram = anchor.workspace.VerificationOracle as Program<VerificationOracle>;
 
   // Test accounts
   let admin: Keypair;
@@ -195,13 +207,13 @@ describe("credential-manager", () => {
 
   describe("register_issuer", () => {
     let issuerAuthority: Keypair;
-    let issuerIdentity: Keypair;
+    let issuerIdentityPda: PublicKey;
     let issuerPda: PublicKey;
 
     before(async () => {
       issuerAuthority = Keypair.generate();
-      issuerIdentity = Keypair.generate();
       await airdrop(issuerAuthority.publicKey, 5);
+      issuerIdentityPda = await createIdentity(registryProgram, issuerAuthority);
 
       [issuerPda] = PublicKey.findProgramAddressSync(
         [Buffer.from("issuer"), issuerAuthority.publicKey.toBuffer()],
@@ -218,7 +230,8 @@ describe("credential-manager", () => {
         .accounts({
           config: configPda,
           issuer: issuerPda,
-          identity: issuerIdentity.publicKey,
+          identity: issuerIdentityPda,
+          orgMember: null,
           authority: issuerAuthority.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -227,7 +240,7 @@ describe("credential-manager", () => {
 
       const issuer = await program.account.credentialIssuer.fetch(issuerPda);
       expect(issuer.authority.toString()).to.equal(issuerAuthority.publicKey.toString());
-      expect(issuer.identity.toString()).to.equal(issuerIdentity.publicKey.toString());
+      expect(issuer.identity.toString()).to.equal(issuerIdentityPda.toString());
       expect(issuer.name).to.equal(issuerName);
       expect(issuer.verificationLevel).to.equal(verificationLevel);
       expect(issuer.credentialsIssued.toNumber()).to.equal(0);
@@ -243,6 +256,7 @@ describe("credential-manager", () => {
     let schemaCreator: Keypair;
     let issuerAuthority: Keypair;
     let issuerPda: PublicKey;
+    let issuerIdentityPda: PublicKey;
     let holder: Keypair;
     let credentialId: Uint8Array;
     let credentialPda: PublicKey;
@@ -285,12 +299,15 @@ describe("credential-manager", () => {
         program.programId
       );
 
+      issuerIdentityPda = await createIdentity(registryProgram, issuerAuthority);
+
       await program.methods
         .registerIssuer("Income Tax Department", 3) // verification level 3 >= required 2
         .accounts({
           config: configPda,
           issuer: issuerPda,
-          identity: Keypair.generate().publicKey,
+          identity: issuerIdentityPda,
+          orgMember: null,
           authority: issuerAuthority.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -324,6 +341,8 @@ describe("credential-manager", () => {
           issuer: issuerPda,
           credential: credentialPda,
           holder: holder.publicKey,
+          issuerIdentity: issuerIdentityPda,
+          orgMember: null,
           authority: issuerAuthority.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -388,6 +407,8 @@ describe("credential-manager", () => {
             issuer: issuerPda, // Issuer has verification level 3
             credential: newCredPda,
             holder: holder.publicKey,
+            issuerIdentity: issuerIdentityPda,
+            orgMember: null,
             authority: issuerAuthority.publicKey,
             systemProgram: SystemProgram.programId,
           })
@@ -423,6 +444,8 @@ describe("credential-manager", () => {
             issuer: issuerPda,
             credential: impersonatedCredPda,
             holder: holder.publicKey,
+            issuerIdentity: issuerIdentityPda,
+            orgMember: null,
             authority: impersonator.publicKey,
             systemProgram: SystemProgram.programId,
           })
@@ -435,6 +458,179 @@ describe("credential-manager", () => {
     });
   });
 
+  describe("organization issuers", () => {
+    const ADMIN = 1 << 0;
+    const ISSUER_OPERATOR = 1 << 1;
+
+    let schemaPda: PublicKey;
+    let orgAuthority: Keypair;
+    let orgIdentityPda: PublicKey;
+    let operator: Keypair;
+    let operatorMemberPda: PublicKey;
+    let issuerPda: PublicKey;
+
+    // Helper to issue a credential on behalf of the organization
+    const issueAsOperator = async (): Promise<PublicKey> => {
+      const credentialId = generateId();
+      const [credentialPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("credential"), credentialId],
+        program.programId
+      );
+
+      await program.methods
+        .issueCredential(
+          Array.from(credentialId),
+          Array.from(generateId()),
+          null,
+          "https://example.com/org-credential.json"
+        )
+        .accounts({
+          config: configPda,
+          schema: schemaPda,
+          issuer: issuerPda,
+          credential: credentialPda,
+          holder: Keypair.generate().publicKey,
+          issuerIdentity: orgIdentityPda,
+          orgMember: operatorMemberPda,
+          authority: operator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+
+      return credentialPda;
+    };
+
+    before(async () => {
+      const schemaId = generateId();
+      [schemaPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("schema"), schemaId],
+        program.programId
+      );
+
+      await program.methods
+        .createSchema(Array.from(schemaId), "EmploymentVerification", 1, 1, false, true)
+        .accounts({
+          config: configPda,
+          schema: schemaPda,
+          creator: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+      // The organization identity and its issuer-operator member
+      orgAuthority = Keypair.generate();
+      await airdrop(orgAuthority.publicKey, 5);
+      orgIdentityPda = await createIdentity(registryProgram, orgAuthority);
+
+      await registryProgram.methods
+        .registerOrganization()
+        .accounts({
+          identityAccount: orgIdentityPda,
+          authority: orgAuthority.publicKey,
+        })
+        .signers([orgAuthority])
+        .rpc();
+
+      operator = Keypair.generate();
+      await airdrop(operator.publicKey, 5);
+
+      [operatorMemberPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("org_member"), orgIdentityPda.toBuffer(), operator.publicKey.toBuffer()],
+        registryProgram.programId
+      );
+
+      await registryProgram.methods
+        .addOrgMember(operator.publicKey, ISSUER_OPERATOR)
+        .accounts({
+          identityAccount: orgIdentityPda,
+          orgMember: operatorMemberPda,
+          adminMembership: null,
+          admin: orgAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([orgAuthority])
+        .rpc();
+
+      [issuerPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("issuer"), operator.publicKey.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("should reject an issuer registration by a non-member", async () => {
+      const outsider = Keypair.generate();
+      await airdrop(outsider.publicKey, 1);
+
+      const [outsiderIssuerPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("issuer"), outsider.publicKey.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .registerIssuer("Not A Member", 1)
+          .accounts({
+            config: configPda,
+            issuer: outsiderIssuerPda,
+            identity: orgIdentityPda,
+            orgMember: null,
+            authority: outsider.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([outsider])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("UnauthorizedIssuer");
+      }
+    });
+
+    it("should let an issuer operator register and issue for the organization", async () => {
+      await program.methods
+        .registerIssuer("Acme Corp HR", 1)
+        .accounts({
+          config: configPda,
+          issuer: issuerPda,
+          identity: orgIdentityPda,
+          orgMember: operatorMemberPda,
+          authority: operator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([operator])
+        .rpc();
+
+      const issuer = await program.account.credentialIssuer.fetch(issuerPda);
+      expect(issuer.identity.toString()).to.equal(orgIdentityPda.toString());
+      expect(issuer.authority.toString()).to.equal(operator.publicKey.toString());
+
+      const credentialPda = await issueAsOperator();
+      const credential = await program.account.credential.fetch(credentialPda);
+      expect(credential.issuer.toString()).to.equal(operator.publicKey.toString());
+    });
+
+    it("should stop issuing once the operator role is withdrawn", async () => {
+      await registryProgram.methods
+        .updateOrgMember(ADMIN)
+        .accounts({
+          identityAccount: orgIdentityPda,
+          orgMember: operatorMemberPda,
+          adminMembership: null,
+          admin: orgAuthority.publicKey,
+        })
+        .signers([orgAuthority])
+        .rpc();
+
+      try {
+        await issueAsOperator();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("UnauthorizedIssuer");
+      }
+    });
+  });
+
   describe("revoke_credential", () => {
     let schemaId: Uint8Array;
     let schemaPda: PublicKey;
@@ -481,12 +677,15 @@ describe("credential-manager", () => {
         program.programId
       );
 
+      const issuerIdentityPda = await createIdentity(registryProgram, issuerAuthority);
+
       await program.methods
         .registerIssuer("Test Issuer", 2)
         .accounts({
           config: configPda,
           issuer: issuerPda,
-          identity: Keypair.generate().publicKey,
+          identity: issuerIdentityPda,
+          orgMember: null,
           authority: issuerAuthority.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -513,6 +712,8 @@ describe("credential-manager", () => {
           issuer: issuerPda,
           credential: credentialPda,
           holder: Keypair.generate().publicKey,
+          issuerIdentity: issuerIdentityPda,
+          orgMember: null,
           authority: issuerAuthority.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -598,12 +799,15 @@ describe("credential-manager", () => {
         program.programId
       );
 
+      const issuerIdentityPda = await createIdentity(registryProgram, issuerAuthority);
+
       await program.methods
         .registerIssuer("Suspend Test Issuer", 2)
         .accounts({
           config: configPda,
           issuer: issuerPda,
-          identity: Keypair.generate().publicKey,
+          identity: issuerIdentityPda,
+          orgMember: null,
           authority: issuerAuthority.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -624,6 +828,8 @@ describe("credential-manager", () => {
           issuer: issuerPda,
           credential: credentialPda,
           holder: Keypair.generate().publicKey,
+          issuerIdentity: issuerIdentityPda,
+          orgMember: null,
           authority: issuerAuthority.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -664,6 +870,8 @@ describe("credential-manager", () => {
 
   describe("verify_credential", () => {
     let credentialPda: PublicKey;
+    let holder: Keypair;
+    let holderIdentityPda: PublicKey;
 
     before(async () => {
       // Use an existing credential from previous tests
@@ -695,18 +903,25 @@ describe("credential-manager", () => {
         program.programId
       );
 
+      const issuerIdentityPda = await createIdentity(registryProgram, issuerAuthority);
+
       await program.methods
         .registerIssuer("Verify Test Issuer", 2)
         .accounts({
           config: configPda,
           issuer: issuerPda,
-          identity: Keypair.generate().publicKey,
+          identity: issuerIdentityPda,
+          orgMember: null,
           authority: issuerAuthority.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([issuerAuthority])
         .rpc();
 
+      holder = Keypair.generate();
+      await airdrop(holder.publicKey, 1);
+      holderIdentityPda = await createIdentity(registryProgram, holder);
+
       const credentialId = generateId();
       [credentialPda] = PublicKey.findProgramAddressSync(
         [Buffer.from("credential"), credentialId],
@@ -720,7 +935,9 @@ describe("credential-manager", () => {
           schema: schemaPda,
           issuer: issuerPda,
           credential: credentialPda,
-          holder: Keypair.generate().publicKey,
+          holder: holderIdentityPda,
+          issuerIdentity: issuerIdentityPda,
+          orgMember: null,
           authority: issuerAuthority.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -737,11 +954,83 @@ describe("credential-manager", () => {
         .verifyCredential()
         .accounts({
           credential: credentialPda,
+          holderIdentity: holderIdentityPda,
+          consent: null,
           verifier: verifier.publicKey,
         })
         .signers([verifier])
         .rpc();
     });
+
+    it("should require a consent grant once the holder opts into strict mode", async () => {
+      const verifier = Keypair.generate();
+      await airdrop(verifier.publicKey, 1);
+
+      await registryProgram.methods
+        .setStrictConsent(true)
+        .accounts({
+          identityAccount: holderIdentityPda,
+          authority: holder.publicKey,
+        })
+        .signers([holder])
+        .rpc();
+
+      try {
+        await program.methods
+          .verifyCredential()
+          .accounts({
+            credential: credentialPda,
+            holderIdentity: holderIdentityPda,
+            consent: null,
+            verifier: verifier.publicKey,
+          })
+          .signers([verifier])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("ConsentRequired");
+      }
+
+      const [consentPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("consent"), holderIdentityPda.toBuffer(), verifier.publicKey.toBuffer()],
+        registryProgram.programId
+      );
+
+      const grantConsent = (verificationMask: anchor.BN) =>
+        registryProgram.methods
+          .grantConsent(verifier.publicKey, verificationMask, 1, new anchor.BN(Math.floor(Date.now() / 1000) + 3600))
+          .accounts({
+            identityAccount: holderIdentityPda,
+            consent: consentPda,
+            authority: holder.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([holder])
+          .rpc();
+      const verifyWithConsent = () =>
+        program.methods
+          .verifyCredential()
+          .accounts({
+            credential: credentialPda,
+            holderIdentity: holderIdentityPda,
+            consent: consentPda,
+            verifier: verifier.publicKey,
+          })
+          .signers([verifier])
+          .rpc();
+
+      // A grant that does not include the credential-verification scope is not enough
+      await grantConsent(new anchor.BN(0));
+      try {
+        await verifyWithConsent();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("ConsentRequired");
+      }
+
+      await grantConsent(CREDENTIAL_VERIFICATION_SCOPE);
+      await verifyWithConsent();
+    });
   });
 
   describe("transfer_credential", () => {
@@ -749,6 +1038,7 @@ describe("credential-manager", () => {
     let issuerAuthority: Keypair;
     let issuerPda: PublicKey;
     let holder: Keypair;
+    let holderIdentityPda: PublicKey;
     let credentialPda: PublicKey;
 
     before(async () => {
@@ -780,12 +1070,15 @@ describe("credential-manager", () => {
         program.programId
       );
 
+      const issuerIdentityPda = await createIdentity(registryProgram, issuerAuthority);
+
       await program.methods
         .registerIssuer("Transfer Test Issuer", 2)
         .accounts({
           config: configPda,
           issuer: issuerPda,
-          identity: Keypair.generate().publicKey,
+          identity: issuerIdentityPda,
+          orgMember: null,
           authority: issuerAuthority.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -794,6 +1087,7 @@ describe("credential-manager", () => {
 
       holder = Keypair.generate();
       await airdrop(holder.publicKey, 1);
+      holderIdentityPda = await createIdentity(registryProgram, holder);
 
       const credentialId = generateId();
       [credentialPda] = PublicKey.findProgramAddressSync(
@@ -808,7 +1102,9 @@ describe("credential-manager", () => {
           schema: schemaPda,
           issuer: issuerPda,
           credential: credentialPda,
-          holder: holder.publicKey,
+          holder: holderIdentityPda,
+          issuerIdentity: issuerIdentityPda,
+          orgMember: null,
           authority: issuerAuthority.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -825,6 +1121,7 @@ describe("credential-manager", () => {
           .accounts({
             schema: schemaPda,
             credential: credentialPda,
+            holderIdentity: holderIdentityPda,
             holder: holder.publicKey,
             newHolder: newHolder.publicKey,
           })
@@ -835,6 +1132,52 @@ describe("credential-manager", () => {
         expect(error.error.errorCode.code).to.equal("CredentialNotTransferable");
       }
     });
+
+    it("should stop a frozen holder from presenting the credential", async () => {
+      const presentCredential = () =>
+        program.methods
+          .presentCredential()
+          .accounts({
+            credential: credentialPda,
+            holderIdentity: holderIdentityPda,
+            presenter: holder.publicKey,
+            delegate: null,
+          })
+          .signers([holder])
+          .rpc();
+
+      await presentCredential();
+
+      // Compliance holds are placed by the registry admin or its compliance officer
+      await initializeRegistryConfig(registryProgram, oracleProgram.programId);
+      const registryConfigPda = findIdentityConfigPDA(registryProgram.programId)[0];
+      await registryProgram.methods
+        .freezeIdentity(1, Array.from(generateHash()))
+        .accounts({
+          config: registryConfigPda,
+          identityAccount: holderIdentityPda,
+          officer: REGISTRY_CONFIG.admin.publicKey,
+        })
+        .signers([REGISTRY_CONFIG.admin])
+        .rpc();
+
+      try {
+        await presentCredential();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("HolderIdentityNotActive");
+      }
+
+      await registryProgram.methods
+        .unfreezeIdentity()
+        .accounts({
+          config: registryConfigPda,
+          identityAccount: holderIdentityPda,
+          officer: REGISTRY_CONFIG.admin.publicKey,
+        })
+        .signers([REGISTRY_CONFIG.admin])
+        .rpc();
+    });
   });
 
   describe("admin functions", () => {
@@ -870,12 +1213,15 @@ describe("credential-manager", () => {
         program.programId
       );
 
+      const issuerIdentityPda = await createIdentity(registryProgram, issuerAuthority);
+
       await program.methods
         .registerIssuer("Admin Test Issuer", 2)
         .accounts({
           config: configPda,
           issuer: issuerPda,
-          identity: Keypair.generate().publicKey,
+          identity: issuerIdentityPda,
+          orgMember: null,
           authority: issuerAuthority.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -1,33 +1,49 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { IdentityRegistry } from "../target/types/identity_registry";
+import { VerificationOracle } from "../target/types/verification_oracle";
+import { StakingManager } from "../target/types/staking_manager";
 import { expect } from "chai";
 import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import * as crypto from "crypto";
+import {
+  createIdentity,
+  findAuthorityIndexPDA,
+  findDidIndexPDA,
+  findIdentityPDA,
+  generateDID,
+  generateHash,
+  generateId,
+  initializeOracleConfig,
+  initializeRegistryConfig,
+  initializeStakingPool,
+  REGISTRY_CONFIG,
+} from "./utils/test-helpers";
 
 describe("identity-registry", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
   const program = anchor.workspace.IdentityRegistry as Program<IdentityRegistry>;
+  const oracleProgram = anchor.workspace.VerificationOracle as Program<VerificationOracle>;
+  const stakingProgram = anchor.workspace.StakingManager as Program<StakingManager>;
 
   let configPda: PublicKey;
   let configBump: number;
   let admin: Keypair;
 
-  const verificationOracle = Keypair.generate();
-  const credentialManager = Keypair.generate().publicKey;
-  const reputationEngine = Keypair.generate().publicKey;
-  const stakingManager = Keypair.generate().publicKey;
+  // Verification results arrive by CPI signed with the oracle program's config PDA
+  const [verificationOracle] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    oracleProgram.programId
+  );
 
-  before(async () => {
-    admin = Keypair.generate();
+  // The config is shared with the credential suite, so its parameters come from the test helpers
+  const { credentialManager, reputationEngine, stakingManager } = REGISTRY_CONFIG;
+  const TOMBSTONE_PERIOD = REGISTRY_CONFIG.tombstonePeriod;
 
-    // Airdrop SOL to admin
-    const signature = await provider.connection.requestAirdrop(
-      admin.publicKey,
-      10 * anchor.web3.LAMPORTS_PER_SOL
-    );
-    await provider.connection.confirmTransaction(signature);
+  before(async () => {
+    admin = REGISTRY_CONFIG.admin;
 
     // Derive config PDA
     [configPda, configBump] = PublicKey.findProgramAddressSync(
@@ -38,37 +54,194 @@ describe("identity-registry", () => {
 
   describe("initialize_config", () => {
     it("should initialize the global config", async () => {
+      await initializeRegistryConfig(program, oracleProgram.programId);
+
+      const config = await program.account.globalConfig.fetch(configPda);
+      expect(config.admin.toString()).to.equal(admin.publicKey.toString());
+      expect(config.verificationOracle.toString()).to.equal(verificationOracle.toString());
+      expect(config.credentialManager.toString()).to.equal(credentialManager.toString());
+      expect(config.reputationEngine.toString()).to.equal(reputationEngine.toString());
+      expect(config.stakingManager.toString()).to.equal(stakingManager.toString());
+      expect(config.minStakeAmount.toNumber()).to.equal(1_000_000_000);
+      expect(config.verificationFee.toNumber()).to.equal(10_000_000);
+      expect(config.tombstonePeriod.toNumber()).to.equal(TOMBSTONE_PERIOD);
+    });
+
+    it("should not migrate a config that already has the tombstone period", async () => {
+      try {
+        await program.methods
+          .migrateConfig()
+          .accounts({
+            config: configPda,
+            payer: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("NotLegacyConfig");
+      }
+    });
+  });
+
+  describe("update_config", () => {
+    const emptyUpdate = {
+      verificationOracle: null,
+      credentialManager: null,
+      reputationEngine: null,
+      stakingManager: null,
+      minStakeAmount: null,
+      verificationFee: null,
+      tombstonePeriod: null,
+      complianceOfficer: null,
+      pseudonymAttester: null,
+    };
+
+    it("should update only the fields that are set", async () => {
       await program.methods
-        .initializeConfig(
-          verificationOracle.publicKey,
-          credentialManager,
-          reputationEngine,
-          stakingManager
-        )
+        .updateConfig({ ...emptyUpdate, verificationFee: new anchor.BN(20_000_000) })
         .accounts({
           config: configPda,
           admin: admin.publicKey,
-          systemProgram: SystemProgram.programId,
         })
         .signers([admin])
         .rpc();
 
       const config = await program.account.globalConfig.fetch(configPda);
-      expect(config.admin.toString()).to.equal(admin.publicKey.toString());
-      expect(config.verificationOracle.toString()).to.equal(
-        verificationOracle.publicKey.toString()
-      );
-      expect(config.credentialManager.toString()).to.equal(credentialManager.toString());
-      expect(config.reputationEngine.toString()).to.equal(reputationEngine.toString());
-      expect(config.stakingManager.toString()).to.equal(stakingManager.toString());
+      expect(config.verificationFee.toNumber()).to.equal(20_000_000);
       expect(config.minStakeAmount.toNumber()).to.equal(1_000_000_000);
-      expect(config.verificationFee.toNumber()).to.equal(10_000_000);
+      expect(config.verificationOracle.toString()).to.equal(verificationOracle.toString());
+    });
+
+    it("should reject a negative tombstone period", async () => {
+      try {
+        await program.methods
+          .updateConfig({ ...emptyUpdate, tombstonePeriod: new anchor.BN(-1) })
+          .accounts({
+            config: configPda,
+            admin: admin.publicKey,
+          })
+          .signers([admin])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidConfigValue");
+      }
+    });
+
+    it("should reject updates from a non-admin", async () => {
+      const stranger = Keypair.generate();
+
+      try {
+        await program.methods
+          .updateConfig({ ...emptyUpdate, verificationFee: new anchor.BN(0) })
+          .accounts({
+            config: configPda,
+            admin: stranger.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("ConstraintHasOne");
+      }
+    });
+
+    it("should set and clear a verification validity period", async () => {
+      await program.methods
+        .setVerificationValidity(7, new anchor.BN(3600))
+        .accounts({
+          config: configPda,
+          admin: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+
+      let config = await program.account.globalConfig.fetch(configPda);
+      expect(config.verificationValidity[7].toNumber()).to.equal(3600);
+
+      await program.methods
+        .setVerificationValidity(7, new anchor.BN(0))
+        .accounts({
+          config: configPda,
+          admin: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+
+      config = await program.account.globalConfig.fetch(configPda);
+      expect(config.verificationValidity[7].toNumber()).to.equal(0);
+    });
+
+    it("should hand the admin role over in two steps", async () => {
+      const newAdmin = Keypair.generate();
+
+      await program.methods
+        .transferAdmin(newAdmin.publicKey)
+        .accounts({
+          config: configPda,
+          admin: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+
+      let config = await program.account.globalConfig.fetch(configPda);
+      expect(config.admin.toString()).to.equal(admin.publicKey.toString());
+      expect(config.pendingAdmin.toString()).to.equal(newAdmin.publicKey.toString());
+
+      const stranger = Keypair.generate();
+      try {
+        await program.methods
+          .acceptAdmin()
+          .accounts({
+            config: configPda,
+            newAdmin: stranger.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("NoPendingAdminTransfer");
+      }
+
+      await program.methods
+        .acceptAdmin()
+        .accounts({
+          config: configPda,
+          newAdmin: newAdmin.publicKey,
+        })
+        .signers([newAdmin])
+        .rpc();
+
+      config = await program.account.globalConfig.fetch(configPda);
+      expect(config.admin.toString()).to.equal(newAdmin.publicKey.toString());
+      expect(config.pendingAdmin).to.be.null;
+
+      // Hand the role back so later suites keep using the original admin
+      await program.methods
+        .transferAdmin(admin.publicKey)
+        .accounts({
+          config: configPda,
+          admin: newAdmin.publicKey,
+        })
+        .signers([newAdmin])
+        .rpc();
+      await program.methods
+        .acceptAdmin()
+        .accounts({
+          config: configPda,
+          newAdmin: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
     });
   });
 
   describe("create_identity", () => {
     let user: Keypair;
+    let identityId: Uint8Array;
     let identityPda: PublicKey;
+    let authorityIndexPda: PublicKey;
 
     beforeEach(async () => {
       user = Keypair.generate();
@@ -80,22 +253,25 @@ describe("identity-registry", () => {
       );
       await provider.connection.confirmTransaction(signature);
 
-      [identityPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("identity"), user.publicKey.toBuffer()],
-        program.programId
-      );
+      identityId = generateId();
+      [identityPda] = findIdentityPDA(identityId, program.programId);
+      [authorityIndexPda] = findAuthorityIndexPDA(user.publicKey, program.programId);
     });
 
     it("should create a new identity", async () => {
-      const did = `did:aadhaar:${user.publicKey.toString().slice(0, 20)}`;
+      const did = generateDID(user.publicKey);
       const metadataUri = "https://example.com/metadata.json";
       const recoveryKeys = [Keypair.generate().publicKey];
 
       await program.methods
-        .createIdentity(did, metadataUri, recoveryKeys)
+        .createIdentity(Array.from(identityId), did, metadataUri, recoveryKeys, 1)
         .accounts({
           identityAccount: identityPda,
+          authorityIndex: authorityIndexPda,
+          didIndex: findDidIndexPDA(did, program.programId)[0],
+          sponsorAccount: null,
           authority: user.publicKey,
+          payer: user.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([user])
@@ -103,11 +279,16 @@ describe("identity-registry", () => {
 
       const identity = await program.account.identityAccount.fetch(identityPda);
       expect(identity.authority.toString()).to.equal(user.publicKey.toString());
+      expect(Buffer.from(identity.identityId)).to.deep.equal(Buffer.from(identityId));
       expect(identity.did).to.equal(did);
       expect(identity.verificationBitmap.toNumber()).to.equal(0);
       expect(identity.reputationScore.toNumber()).to.equal(500);
       expect(identity.stakedAmount.toNumber()).to.equal(0);
       expect(identity.recoveryKeys.length).to.equal(1);
+      expect(identity.recoveryThreshold).to.equal(1);
+
+      const authorityIndex = await program.account.authorityIndex.fetch(authorityIndexPda);
+      expect(authorityIndex.identity.toString()).to.equal(identityPda.toString());
     });
 
     it("should reject DID that is too long", async () => {
@@ -115,10 +296,14 @@ describe("identity-registry", () => {
 
       try {
         await program.methods
-          .createIdentity(longDid, "https://example.com", [])
+          .createIdentity(Array.from(identityId), longDid, "https://example.com", [], 0)
           .accounts({
             identityAccount: identityPda,
+            authorityIndex: authorityIndexPda,
+            didIndex: findDidIndexPDA(longDid, program.programId)[0],
+            sponsorAccount: null,
             authority: user.publicKey,
+            payer: user.publicKey,
             systemProgram: SystemProgram.programId,
           })
           .signers([user])
@@ -130,14 +315,19 @@ describe("identity-registry", () => {
     });
 
     it("should reject too many recovery keys", async () => {
+      const did = generateDID(user.publicKey);
       const tooManyKeys = Array(6).fill(null).map(() => Keypair.generate().publicKey);
 
       try {
         await program.methods
-          .createIdentity("did:test", "https://example.com", tooManyKeys)
+          .createIdentity(Array.from(identityId), did, "https://example.com", tooManyKeys, 1)
           .accounts({
             identityAccount: identityPda,
+            authorityIndex: authorityIndexPda,
+            didIndex: findDidIndexPDA(did, program.programId)[0],
+            sponsorAccount: null,
             authority: user.publicKey,
+            payer: user.publicKey,
             systemProgram: SystemProgram.programId,
           })
           .signers([user])
@@ -147,11 +337,36 @@ describe("identity-registry", () => {
         expect(error.error.errorCode.code).to.equal("TooManyRecoveryKeys");
       }
     });
+
+    it("should keep one identity per authority", async () => {
+      await createIdentity(program, user);
+      const did = `${generateDID(user.publicKey)}:second`;
+
+      try {
+        await program.methods
+          .createIdentity(Array.from(identityId), did, "https://example.com", [], 0)
+          .accounts({
+            identityAccount: identityPda,
+            authorityIndex: authorityIndexPda,
+            didIndex: findDidIndexPDA(did, program.programId)[0],
+            sponsorAccount: null,
+            authority: user.publicKey,
+            payer: user.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("already in use");
+      }
+    });
   });
 
-  describe("update_verification_status", () => {
+  describe("resolve_did", () => {
     let user: Keypair;
     let identityPda: PublicKey;
+    let did: string;
 
     before(async () => {
       user = Keypair.generate();
@@ -162,78 +377,142 @@ describe("identity-registry", () => {
       );
       await provider.connection.confirmTransaction(signature);
 
-      [identityPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("identity"), user.publicKey.toBuffer()],
-        program.programId
-      );
+      did = generateDID(user.publicKey);
+      identityPda = await createIdentity(program, user, [], did);
+    });
 
-      await program.methods
-        .createIdentity("did:test:verification", "https://example.com", [])
+    it("should resolve a DID to its identity", async () => {
+      const resolved = await program.methods
+        .resolveDid(did)
         .accounts({
+          didIndex: findDidIndexPDA(did, program.programId)[0],
           identityAccount: identityPda,
-          authority: user.publicKey,
-          systemProgram: SystemProgram.programId,
         })
-        .signers([user])
-        .rpc();
+        .view();
+
+      expect(resolved.toString()).to.equal(identityPda.toString());
     });
 
-    it("should allow the configured verification oracle to update verification status", async () => {
+    it("should reject a DID that is already registered", async () => {
+      const otherUser = Keypair.generate();
+      const signature = await provider.connection.requestAirdrop(
+        otherUser.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      try {
+        await createIdentity(program, otherUser, [], did);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("already in use");
+      }
+    });
+  });
+
+  describe("did_document", () => {
+    let user: Keypair;
+    let identityPda: PublicKey;
+
+    const AUTHENTICATION = 1 << 0;
+    const ASSERTION_METHOD = 1 << 1;
+
+    before(async () => {
+      user = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        user.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      identityPda = await createIdentity(program, user);
+    });
+
+    it("should add a verification method and grow the account", async () => {
+      const sizeBefore = (await provider.connection.getAccountInfo(identityPda)).data.length;
+      const methodKey = Keypair.generate().publicKey;
+
       await program.methods
-        .updateVerificationStatus(0, true)
+        .addVerificationMethod("key-1", methodKey, AUTHENTICATION | ASSERTION_METHOD)
         .accounts({
           identityAccount: identityPda,
-          oracle: verificationOracle.publicKey,
-          config: configPda,
+          authority: user.publicKey,
+          systemProgram: SystemProgram.programId,
         })
-        .signers([verificationOracle])
+        .signers([user])
         .rpc();
 
       const identity = await program.account.identityAccount.fetch(identityPda);
-      expect(identity.verificationBitmap.toNumber()).to.equal(1);
-    });
+      expect(identity.verificationMethods.length).to.equal(1);
+      expect(identity.verificationMethods[0].id).to.equal("key-1");
+      expect(identity.verificationMethods[0].publicKey.toString()).to.equal(methodKey.toString());
+      expect(identity.verificationMethods[0].purposes).to.equal(AUTHENTICATION | ASSERTION_METHOD);
 
-    it("should reject unauthorized verification updates", async () => {
-      const unauthorizedOracle = Keypair.generate();
+      const sizeAfter = (await provider.connection.getAccountInfo(identityPda)).data.length;
+      expect(sizeAfter).to.be.greaterThan(sizeBefore);
+    });
 
+    it("should reject a duplicate verification method id", async () => {
       try {
         await program.methods
-          .updateVerificationStatus(1, true)
+          .addVerificationMethod("key-1", Keypair.generate().publicKey, AUTHENTICATION)
           .accounts({
             identityAccount: identityPda,
-            oracle: unauthorizedOracle.publicKey,
-            config: configPda,
+            authority: user.publicKey,
+            systemProgram: SystemProgram.programId,
           })
-          .signers([unauthorizedOracle])
+          .signers([user])
           .rpc();
         expect.fail("Should have thrown an error");
       } catch (error: any) {
-        expect(error.error.errorCode.code).to.equal("UnauthorizedOracle");
+        expect(error.error.errorCode.code).to.equal("VerificationMethodAlreadyExists");
       }
     });
-  });
 
-  describe("add_recovery_key", () => {
-    let user: Keypair;
-    let identityPda: PublicKey;
+    it("should reject a verification method without purposes", async () => {
+      try {
+        await program.methods
+          .addVerificationMethod("key-2", Keypair.generate().publicKey, 0)
+          .accounts({
+            identityAccount: identityPda,
+            authority: user.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidVerificationMethodPurpose");
+      }
+    });
 
-    before(async () => {
-      user = Keypair.generate();
+    it("should add, update and remove a service endpoint", async () => {
+      await program.methods
+        .addService("hub", "IdentityHub", "https://hub.example.com")
+        .accounts({
+          identityAccount: identityPda,
+          authority: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
 
-      const signature = await provider.connection.requestAirdrop(
-        user.publicKey,
-        2 * anchor.web3.LAMPORTS_PER_SOL
-      );
-      await provider.connection.confirmTransaction(signature);
+      await program.methods
+        .updateService("hub", "IdentityHub", "https://hub2.example.com")
+        .accounts({
+          identityAccount: identityPda,
+          authority: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
 
-      [identityPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("identity"), user.publicKey.toBuffer()],
-        program.programId
-      );
+      let identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.services.length).to.equal(1);
+      expect(identity.services[0].endpoint).to.equal("https://hub2.example.com");
 
-      // Create identity first
       await program.methods
-        .createIdentity("did:test:recovery", "https://example.com", [])
+        .removeService("hub")
         .accounts({
           identityAccount: identityPda,
           authority: user.publicKey,
@@ -241,34 +520,38 @@ describe("identity-registry", () => {
         })
         .signers([user])
         .rpc();
+
+      identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.services.length).to.equal(0);
     });
 
-    it("should add a recovery key", async () => {
-      const newRecoveryKey = Keypair.generate().publicKey;
+    it("should remove a verification method and shrink the account", async () => {
+      const sizeBefore = (await provider.connection.getAccountInfo(identityPda)).data.length;
 
       await program.methods
-        .addRecoveryKey(newRecoveryKey)
+        .removeVerificationMethod("key-1")
         .accounts({
           identityAccount: identityPda,
           authority: user.publicKey,
+          systemProgram: SystemProgram.programId,
         })
         .signers([user])
         .rpc();
 
       const identity = await program.account.identityAccount.fetch(identityPda);
-      expect(identity.recoveryKeys.length).to.equal(1);
-      expect(identity.recoveryKeys[0].toString()).to.equal(newRecoveryKey.toString());
+      expect(identity.verificationMethods.length).to.equal(0);
+
+      const sizeAfter = (await provider.connection.getAccountInfo(identityPda)).data.length;
+      expect(sizeAfter).to.be.lessThan(sizeBefore);
     });
   });
 
-  describe("recover_identity", () => {
+  describe("update_metadata", () => {
     let user: Keypair;
-    let recoveryKey: Keypair;
     let identityPda: PublicKey;
 
     before(async () => {
       user = Keypair.generate();
-      recoveryKey = Keypair.generate();
 
       const signature = await provider.connection.requestAirdrop(
         user.publicKey,
@@ -276,43 +559,1781 @@ describe("identity-registry", () => {
       );
       await provider.connection.confirmTransaction(signature);
 
-      const signature2 = await provider.connection.requestAirdrop(
-        recoveryKey.publicKey,
-        2 * anchor.web3.LAMPORTS_PER_SOL
-      );
-      await provider.connection.confirmTransaction(signature2);
+      identityPda = await createIdentity(program, user);
+    });
 
-      [identityPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("identity"), user.publicKey.toBuffer()],
-        program.programId
-      );
+    it("should store the new URI and content hash and bump the revision", async () => {
+      const metadataHash = Array.from(generateHash());
 
-      // Create identity with recovery key
       await program.methods
-        .createIdentity("did:test:recovery2", "https://example.com", [recoveryKey.publicKey])
+        .updateMetadata("https://example.com/metadata-v2.json", metadataHash)
         .accounts({
           identityAccount: identityPda,
-          authority: user.publicKey,
-          systemProgram: SystemProgram.programId,
+          signer: user.publicKey,
+          delegate: null,
         })
         .signers([user])
         .rpc();
-    });
 
-    it("should recover identity to new authority", async () => {
-      const newAuthority = Keypair.generate().publicKey;
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.metadataUri).to.equal("https://example.com/metadata-v2.json");
+      expect(identity.metadataHash).to.deep.equal(metadataHash);
+      expect(identity.metadataRevision).to.equal(1);
+    });
 
+    it("should fail with a URI that is too long", async () => {
+      try {
+        await program.methods
+          .updateMetadata("https://example.com/" + "a".repeat(256), Array.from(generateHash()))
+          .accounts({
+            identityAccount: identityPda,
+            signer: user.publicKey,
+            delegate: null,
+          })
+          .signers([user])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("URITooLong");
+      }
+    });
+
+    it("should reject updates from anyone but the authority", async () => {
+      const stranger = Keypair.generate();
+
+      try {
+        await program.methods
+          .updateMetadata("https://evil.example.com/metadata.json", Array.from(generateHash()))
+          .accounts({
+            identityAccount: identityPda,
+            signer: stranger.publicKey,
+            delegate: null,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("UnauthorizedSigner");
+      }
+    });
+  });
+
+  describe("deactivate_identity", () => {
+    let user: Keypair;
+    let did: string;
+    let identityPda: PublicKey;
+    let authorityIndexPda: PublicKey;
+    let didIndexPda: PublicKey;
+    const wallet = Keypair.generate();
+    const [linkedWalletPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("linked_wallet"), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+
+    before(async () => {
+      user = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        user.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      did = generateDID(user.publicKey);
+      identityPda = await createIdentity(program, user, [], did);
+      [authorityIndexPda] = findAuthorityIndexPDA(user.publicKey, program.programId);
+      [didIndexPda] = findDidIndexPDA(did, program.programId);
+
+      await program.methods
+        .linkWallet(true)
+        .accounts({
+          identityAccount: identityPda,
+          linkedWallet: linkedWalletPda,
+          wallet: wallet.publicKey,
+          authority: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([wallet, user])
+        .rpc();
+
+      await program.methods
+        .setNominee(Keypair.generate().publicKey, new anchor.BN(90 * 24 * 60 * 60))
+        .accounts({
+          identityAccount: identityPda,
+          authority: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    });
+
+    it("should wipe personal data and leave a tombstone", async () => {
+      const sizeBefore = (await provider.connection.getAccountInfo(identityPda)).data.length;
+
+      await program.methods
+        .deactivateIdentity()
+        .accounts({
+          identityAccount: identityPda,
+          authorityIndex: authorityIndexPda,
+          didIndex: didIndexPda,
+          authority: user.publicKey,
+          rentRecipient: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.status).to.deep.equal({ deactivated: {} });
+      expect(identity.did).to.equal("");
+      expect(identity.metadataUri).to.equal("");
+      expect(identity.recoveryKeys.length).to.equal(0);
+      expect(identity.nominee).to.be.null;
+      expect(identity.deactivatedAt.toNumber()).to.be.greaterThan(0);
+
+      const sizeAfter = (await provider.connection.getAccountInfo(identityPda)).data.length;
+      expect(sizeAfter).to.be.lessThan(sizeBefore);
+
+      const authorityIndex = await program.account.authorityIndex.fetchNullable(authorityIndexPda);
+      expect(authorityIndex).to.be.null;
+
+      const didIndex = await program.account.didIndex.fetch(didIndexPda);
+      expect(didIndex.tombstonedAt.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("should not let a still-linked wallet assert for the deactivated identity", async () => {
+      expect(await program.account.linkedWallet.fetchNullable(linkedWalletPda)).to.not.be.null;
+
+      try {
+        await program.methods
+          .assertIdentity(new anchor.BN(0), new anchor.BN(0), new anchor.BN(0))
+          .accounts({
+            identityAccount: identityPda,
+            linkedWallet: linkedWalletPda,
+            consent: null,
+            relyingParty: null,
+          })
+          .view();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("IdentityDeactivated");
+      }
+    });
+
+    it("should block re-registration of a tombstoned DID", async () => {
+      const squatter = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        squatter.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      try {
+        await createIdentity(program, squatter, [], did);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("already in use");
+      }
+    });
+
+    it("should purge the tombstone and release the DID", async () => {
+      await program.methods
+        .purgeTombstone()
+        .accounts({
+          config: configPda,
+          identityAccount: identityPda,
+          didIndex: didIndexPda,
+          rentRecipient: user.publicKey,
+        })
+        .rpc();
+
+      const identity = await program.account.identityAccount.fetchNullable(identityPda);
+      expect(identity).to.be.null;
+
+      const didIndex = await program.account.didIndex.fetchNullable(didIndexPda);
+      expect(didIndex).to.be.null;
+
+      const newIdentityPda = await createIdentity(program, user, [], did);
+      const newIdentity = await program.account.identityAccount.fetch(newIdentityPda);
+      expect(newIdentity.did).to.equal(did);
+    });
+
+    it("should not purge an active identity", async () => {
+      const activeUser = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        activeUser.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      const activeDid = generateDID(activeUser.publicKey);
+      const activeIdentityPda = await createIdentity(program, activeUser, [], activeDid);
+      const [activeDidIndexPda] = findDidIndexPDA(activeDid, program.programId);
+
+      try {
+        await program.methods
+          .purgeTombstone()
+          .accounts({
+            config: configPda,
+            identityAccount: activeIdentityPda,
+            didIndex: activeDidIndexPda,
+            rentRecipient: activeUser.publicKey,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("IdentityNotDeactivated");
+      }
+    });
+  });
+
+  describe("assert_identity", () => {
+    let user: Keypair;
+    let identityPda: PublicKey;
+
+    before(async () => {
+      user = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        user.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      identityPda = await createIdentity(program, user);
+    });
+
+    it("should return the identity status when requirements are met", async () => {
+      const assertion = await program.methods
+        .assertIdentity(new anchor.BN(0), new anchor.BN(500), new anchor.BN(0))
+        .accounts({
+          identityAccount: identityPda,
+          linkedWallet: null,
+          consent: null,
+          relyingParty: null,
+        })
+        .view();
+
+      expect(assertion.identity.toString()).to.equal(identityPda.toString());
+      expect(assertion.authority.toString()).to.equal(user.publicKey.toString());
+      expect(assertion.wallet.toString()).to.equal(user.publicKey.toString());
+      expect(assertion.reputationScore.toNumber()).to.equal(500);
+      expect(assertion.status).to.deep.equal({ active: {} });
+    });
+
+    it("should fail when a required verification is missing", async () => {
+      try {
+        await program.methods
+          .assertIdentity(new anchor.BN(1 << 1), new anchor.BN(0), new anchor.BN(0))
+          .accounts({
+            identityAccount: identityPda,
+            linkedWallet: null,
+            consent: null,
+            relyingParty: null,
+          })
+          .view();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("MissingRequiredVerification");
+      }
+    });
+
+    it("should fail below the minimum reputation or stake", async () => {
+      try {
+        await program.methods
+          .assertIdentity(new anchor.BN(0), new anchor.BN(501), new anchor.BN(0))
+          .accounts({
+            identityAccount: identityPda,
+            linkedWallet: null,
+            consent: null,
+            relyingParty: null,
+          })
+          .view();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InsufficientReputation");
+      }
+
+      try {
+        await program.methods
+          .assertIdentity(new anchor.BN(0), new anchor.BN(0), new anchor.BN(1))
+          .accounts({
+            identityAccount: identityPda,
+            linkedWallet: null,
+            consent: null,
+            relyingParty: null,
+          })
+          .view();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InsufficientStake");
+      }
+    });
+  });
+
+  describe("delegates", () => {
+    const UPDATE_METADATA = 1 << 2;
+
+    let user: Keypair;
+    let delegateKey: Keypair;
+    let identityPda: PublicKey;
+    let delegatePda: PublicKey;
+
+    before(async () => {
+      user = Keypair.generate();
+      delegateKey = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        user.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      identityPda = await createIdentity(program, user);
+      [delegatePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("delegate"), identityPda.toBuffer(), delegateKey.publicKey.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("should reject unknown permission bits", async () => {
+      try {
+        await program.methods
+          .addDelegate(delegateKey.publicKey, 1 << 7, new anchor.BN(Math.floor(Date.now() / 1000) + 3600))
+          .accounts({
+            identityAccount: identityPda,
+            delegateAccount: delegatePda,
+            authority: user.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidDelegatePermissions");
+      }
+    });
+
+    it("should let a delegate update metadata within its permissions", async () => {
+      await program.methods
+        .addDelegate(delegateKey.publicKey, UPDATE_METADATA, new anchor.BN(Math.floor(Date.now() / 1000) + 3600))
+        .accounts({
+          identityAccount: identityPda,
+          delegateAccount: delegatePda,
+          authority: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      const delegate = await program.account.delegate.fetch(delegatePda);
+      expect(delegate.permissions).to.equal(UPDATE_METADATA);
+      expect(delegate.grantedBy.toString()).to.equal(user.publicKey.toString());
+
+      await program.methods
+        .updateMetadata("https://example.com/delegated.json", Array.from(generateHash()))
+        .accounts({
+          identityAccount: identityPda,
+          signer: delegateKey.publicKey,
+          delegate: delegatePda,
+        })
+        .signers([delegateKey])
+        .rpc();
+
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.metadataUri).to.equal("https://example.com/delegated.json");
+    });
+
+    it("should not let another signer use the delegate grant", async () => {
+      const stranger = Keypair.generate();
+      try {
+        await program.methods
+          .updateMetadata("https://example.com/stranger.json", Array.from(generateHash()))
+          .accounts({
+            identityAccount: identityPda,
+            signer: stranger.publicKey,
+            delegate: delegatePda,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("UnauthorizedSigner");
+      }
+    });
+
+    it("should close the delegate account on revoke", async () => {
+      await program.methods
+        .revokeDelegate()
+        .accounts({
+          identityAccount: identityPda,
+          delegateAccount: delegatePda,
+          authority: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const delegate = await program.account.delegate.fetchNullable(delegatePda);
+      expect(delegate).to.be.null;
+    });
+  });
+
+  describe("sponsored creation", () => {
+    let sponsor: Keypair;
+    let sponsorAccountPda: PublicKey;
+
+    // Create an identity for a fresh, unfunded user with `payer` covering the rent
+    const createPaidBy = async (payer: Keypair, sponsorAccount: PublicKey | null) => {
+      const user = Keypair.generate();
+      const identityId = generateId();
+      const did = generateDID(user.publicKey);
+      const [identityPda] = findIdentityPDA(identityId, program.programId);
+
+      await program.methods
+        .createIdentity(Array.from(identityId), did, "https://example.com/metadata.json", [], 0)
+        .accounts({
+          identityAccount: identityPda,
+          authorityIndex: findAuthorityIndexPDA(user.publicKey, program.programId)[0],
+          didIndex: findDidIndexPDA(did, program.programId)[0],
+          sponsorAccount,
+          authority: user.publicKey,
+          payer: payer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user, payer])
+        .rpc();
+
+      return identityPda;
+    };
+
+    before(async () => {
+      sponsor = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        sponsor.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      [sponsorAccountPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("sponsor"), sponsor.publicKey.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("should reject a third-party payer that is not a sponsor", async () => {
+      try {
+        await createPaidBy(sponsor, null);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("UnauthorizedPayer");
+      }
+    });
+
+    it("should let an approved sponsor pay for a new identity", async () => {
+      await program.methods
+        .setSponsor(sponsor.publicKey, new anchor.BN(anchor.web3.LAMPORTS_PER_SOL), 1, new anchor.BN(3600), true)
+        .accounts({
+          config: configPda,
+          sponsorAccount: sponsorAccountPda,
+          admin: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+      const identityPda = await createPaidBy(sponsor, sponsorAccountPda);
+
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.sponsor.toString()).to.equal(sponsor.publicKey.toString());
+
+      const sponsorAccount = await program.account.sponsorAccount.fetch(sponsorAccountPda);
+      expect(sponsorAccount.identitiesSponsored.toNumber()).to.equal(1);
+      expect(sponsorAccount.spent.toNumber()).to.be.greaterThan(0);
+      expect(sponsorAccount.periodCount).to.equal(1);
+    });
+
+    it("should enforce the sponsor rate limit", async () => {
+      try {
+        await createPaidBy(sponsor, sponsorAccountPda);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("SponsorRateLimited");
+      }
+    });
+  });
+
+  describe("linked wallets", () => {
+    let user: Keypair;
+    let identityPda: PublicKey;
+
+    const findLinkedWalletPDA = (wallet: PublicKey): PublicKey =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("linked_wallet"), wallet.toBuffer()],
+        program.programId
+      )[0];
+
+    const linkWallet = async (wallet: Keypair, inheritsVerification: boolean) => {
+      await program.methods
+        .linkWallet(inheritsVerification)
+        .accounts({
+          identityAccount: identityPda,
+          linkedWallet: findLinkedWalletPDA(wallet.publicKey),
+          wallet: wallet.publicKey,
+          authority: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([wallet, user])
+        .rpc();
+    };
+
+    before(async () => {
+      user = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        user.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      identityPda = await createIdentity(program, user);
+    });
+
+    it("should link a wallet signed by both sides", async () => {
+      const wallet = Keypair.generate();
+      await linkWallet(wallet, false);
+
+      const linked = await program.account.linkedWallet.fetch(findLinkedWalletPDA(wallet.publicKey));
+      expect(linked.identity.toString()).to.equal(identityPda.toString());
+      expect(linked.wallet.toString()).to.equal(wallet.publicKey.toString());
+      expect(linked.inheritsVerification).to.equal(false);
+
+      // A wallet that does not inherit verification is asserted with an empty bitmap
+      const assertion = await program.methods
+        .assertIdentity(new anchor.BN(0), new anchor.BN(0), new anchor.BN(0))
+        .accounts({
+          identityAccount: identityPda,
+          linkedWallet: findLinkedWalletPDA(wallet.publicKey),
+          consent: null,
+          relyingParty: null,
+        })
+        .view();
+      expect(assertion.wallet.toString()).to.equal(wallet.publicKey.toString());
+      expect(assertion.verificationBitmap.toNumber()).to.equal(0);
+    });
+
+    it("should not link the authority as its own wallet", async () => {
+      try {
+        await linkWallet(user, true);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("WalletIsAuthority");
+      }
+    });
+
+    it("should let the wallet or the authority unlink, but no one else", async () => {
+      const wallet = Keypair.generate();
+      const stranger = Keypair.generate();
+      await linkWallet(wallet, true);
+
+      try {
+        await program.methods
+          .unlinkWallet()
+          .accounts({
+            linkedWallet: findLinkedWalletPDA(wallet.publicKey),
+            identityAccount: identityPda,
+            signer: stranger.publicKey,
+            payer: user.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("UnauthorizedUnlink");
+      }
+
+      await program.methods
+        .unlinkWallet()
+        .accounts({
+          linkedWallet: findLinkedWalletPDA(wallet.publicKey),
+          identityAccount: null,
+          signer: wallet.publicKey,
+          payer: user.publicKey,
+        })
+        .signers([wallet])
+        .rpc();
+      expect(await program.account.linkedWallet.fetchNullable(findLinkedWalletPDA(wallet.publicKey))).to.be.null;
+
+      await linkWallet(wallet, true);
+      await program.methods
+        .unlinkWallet()
+        .accounts({
+          linkedWallet: findLinkedWalletPDA(wallet.publicKey),
+          identityAccount: identityPda,
+          signer: user.publicKey,
+          payer: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+      expect(await program.account.linkedWallet.fetchNullable(findLinkedWalletPDA(wallet.publicKey))).to.be.null;
+    });
+  });
+
+  describe("guardian", () => {
+    let dependent: Keypair;
+    let guardian: Keypair;
+    let identityPda: PublicKey;
+
+    before(async () => {
+      dependent = Keypair.generate();
+      guardian = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        dependent.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      identityPda = await createIdentity(program, dependent);
+    });
+
+    it("should hand control to the guardian until the handover time", async () => {
+      const guardianUntil = Math.floor(Date.now() / 1000) + 3600;
+
+      await program.methods
+        .assignGuardian(new anchor.BN(guardianUntil))
+        .accounts({
+          identityAccount: identityPda,
+          authority: dependent.publicKey,
+          guardian: guardian.publicKey,
+        })
+        .signers([dependent, guardian])
+        .rpc();
+
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.identityType).to.deep.equal({ dependent: {} });
+      expect(identity.guardian.toString()).to.equal(guardian.publicKey.toString());
+      expect(identity.guardianUntil.toNumber()).to.equal(guardianUntil);
+
+      await program.methods
+        .updateMetadata("https://example.com/guardian.json", Array.from(generateHash()))
+        .accounts({
+          identityAccount: identityPda,
+          signer: guardian.publicKey,
+          delegate: null,
+        })
+        .signers([guardian])
+        .rpc();
+
+      try {
+        await program.methods
+          .updateMetadata("https://example.com/dependent.json", Array.from(generateHash()))
+          .accounts({
+            identityAccount: identityPda,
+            signer: dependent.publicKey,
+            delegate: null,
+          })
+          .signers([dependent])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("UnauthorizedSigner");
+      }
+    });
+
+    it("should not let the guardian transfer the dependent's authority", async () => {
+      try {
+        await program.methods
+          .transferAuthority(guardian.publicKey)
+          .accounts({
+            identityAccount: identityPda,
+            authority: guardian.publicKey,
+          })
+          .signers([guardian])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("GuardianControlled");
+      }
+    });
+
+    it("should not let the dependent claim control before the handover time", async () => {
+      try {
+        await program.methods
+          .claimControl()
+          .accounts({
+            identityAccount: identityPda,
+            authority: dependent.publicKey,
+          })
+          .signers([dependent])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("HandoverNotReached");
+      }
+    });
+
+    it("should let the guardian, not the dependent, unlink a wallet", async () => {
+      const wallet = Keypair.generate();
+      const [linkedWalletPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("linked_wallet"), wallet.publicKey.toBuffer()],
+        program.programId
+      );
+      const signature = await provider.connection.requestAirdrop(
+        guardian.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      await program.methods
+        .linkWallet(false)
+        .accounts({
+          identityAccount: identityPda,
+          linkedWallet: linkedWalletPda,
+          wallet: wallet.publicKey,
+          authority: guardian.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([wallet, guardian])
+        .rpc();
+
+      const unlinkWallet = (signer: Keypair) =>
+        program.methods
+          .unlinkWallet()
+          .accounts({
+            linkedWallet: linkedWalletPda,
+            identityAccount: identityPda,
+            signer: signer.publicKey,
+            payer: guardian.publicKey,
+          })
+          .signers([signer])
+          .rpc();
+
+      try {
+        await unlinkWallet(dependent);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("UnauthorizedUnlink");
+      }
+
+      await unlinkWallet(guardian);
+      expect(await program.account.linkedWallet.fetchNullable(linkedWalletPda)).to.be.null;
+    });
+  });
+
+  describe("enable_multisig", () => {
+    let user: Keypair;
+    let did: string;
+    let identityPda: PublicKey;
+    let multisigPda: PublicKey;
+    let multisigSignerPda: PublicKey;
+    const signers = [Keypair.generate().publicKey, Keypair.generate().publicKey];
+
+    const enableMultisig = (threshold: number) =>
+      program.methods
+        .enableMultisig(signers, threshold)
+        .accounts({
+          identityAccount: identityPda,
+          multisig: multisigPda,
+          multisigSigner: multisigSignerPda,
+          oldAuthorityIndex: findAuthorityIndexPDA(user.publicKey, program.programId)[0],
+          newAuthorityIndex: findAuthorityIndexPDA(multisigSignerPda, program.programId)[0],
+          didIndex: findDidIndexPDA(did, program.programId)[0],
+          authority: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+    before(async () => {
+      user = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        user.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      did = generateDID(user.publicKey);
+      identityPda = await createIdentity(program, user, [], did);
+
+      [multisigPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("multisig"), identityPda.toBuffer()],
+        program.programId
+      );
+      [multisigSignerPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("multisig_signer"), identityPda.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("should reject a threshold above the signer count", async () => {
+      try {
+        await enableMultisig(3);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidMultisigThreshold");
+      }
+    });
+
+    it("should hand the authority to the multisig signer PDA", async () => {
+      await enableMultisig(2);
+
+      const multisig = await program.account.multisigAuthority.fetch(multisigPda);
+      expect(multisig.signers.map((key: PublicKey) => key.toString())).to.deep.equal(
+        signers.map((key) => key.toString())
+      );
+      expect(multisig.threshold).to.equal(2);
+
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.authority.toString()).to.equal(multisigSignerPda.toString());
+
+      const oldIndex = await program.account.authorityIndex.fetchNullable(
+        findAuthorityIndexPDA(user.publicKey, program.programId)[0]
+      );
+      expect(oldIndex).to.be.null;
+
+      const didIndex = await program.account.didIndex.fetch(findDidIndexPDA(did, program.programId)[0]);
+      expect(didIndex.authority.toString()).to.equal(multisigSignerPda.toString());
+    });
+
+    it("should no longer accept the old single key", async () => {
+      try {
+        await program.methods
+          .updateMetadata("https://example.com/single-key.json", Array.from(generateHash()))
+          .accounts({
+            identityAccount: identityPda,
+            signer: user.publicKey,
+            delegate: null,
+          })
+          .signers([user])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("UnauthorizedSigner");
+      }
+    });
+  });
+
+  describe("compliance hold", () => {
+    // The provider wallet is made compliance officer so the oracle suite can place holds too
+    const officer = provider.wallet.publicKey;
+
+    let user: Keypair;
+    let identityPda: PublicKey;
+
+    before(async () => {
+      user = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        user.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      identityPda = await createIdentity(program, user);
+
+      await program.methods
+        .updateConfig({
+          verificationOracle: null,
+          credentialManager: null,
+          reputationEngine: null,
+          stakingManager: null,
+          minStakeAmount: null,
+          verificationFee: null,
+          tombstonePeriod: null,
+          complianceOfficer: officer,
+          pseudonymAttester: null,
+        })
+        .accounts({
+          config: configPda,
+          admin: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+    });
+
+    it("should only let the admin or compliance officer freeze", async () => {
+      const stranger = Keypair.generate();
+
+      try {
+        await program.methods
+          .freezeIdentity(1, Array.from(generateHash()))
+          .accounts({
+            config: configPda,
+            identityAccount: identityPda,
+            officer: stranger.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("UnauthorizedComplianceOfficer");
+      }
+    });
+
+    it("should freeze an identity and block owner actions", async () => {
+      const caseReference = Array.from(generateHash());
+
+      await program.methods
+        .freezeIdentity(7, caseReference)
+        .accounts({
+          config: configPda,
+          identityAccount: identityPda,
+          officer,
+        })
+        .rpc();
+
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.status).to.deep.equal({ frozen: {} });
+      expect(identity.freezeReason).to.equal(7);
+      expect(identity.freezeCase).to.deep.equal(caseReference);
+
+      try {
+        await program.methods
+          .updateMetadata("https://example.com/frozen.json", Array.from(generateHash()))
+          .accounts({
+            identityAccount: identityPda,
+            signer: user.publicKey,
+            delegate: null,
+          })
+          .signers([user])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("IdentityFrozen");
+      }
+    });
+
+    it("should block DID document changes while frozen", async () => {
+      try {
+        await program.methods
+          .addService("hub", "IdentityHub", "https://hub.example.com")
+          .accounts({
+            identityAccount: identityPda,
+            authority: user.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("IdentityFrozen");
+      }
+    });
+
+    it("should lift the hold", async () => {
+      await program.methods
+        .unfreezeIdentity()
+        .accounts({
+          config: configPda,
+          identityAccount: identityPda,
+          officer,
+        })
+        .rpc();
+
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.status).to.deep.equal({ active: {} });
+      expect(identity.freezeReason).to.equal(0);
+    });
+  });
+
+  describe("succession", () => {
+    const MIN_INACTIVITY_THRESHOLD = 90 * 24 * 60 * 60;
+
+    let user: Keypair;
+    let nominee: Keypair;
+    let identityPda: PublicKey;
+
+    before(async () => {
+      user = Keypair.generate();
+      nominee = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        user.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      identityPda = await createIdentity(program, user);
+    });
+
+    it("should reject an inactivity threshold below the minimum", async () => {
+      try {
+        await program.methods
+          .setNominee(nominee.publicKey, new anchor.BN(MIN_INACTIVITY_THRESHOLD - 1))
+          .accounts({
+            identityAccount: identityPda,
+            authority: user.publicKey,
+          })
+          .signers([user])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidInactivityThreshold");
+      }
+    });
+
+    it("should name a nominee and record owner activity", async () => {
+      const previous = await program.account.identityAccount.fetch(identityPda);
+
+      await program.methods
+        .setNominee(nominee.publicKey, new anchor.BN(MIN_INACTIVITY_THRESHOLD))
+        .accounts({
+          identityAccount: identityPda,
+          authority: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.nominee.toString()).to.equal(nominee.publicKey.toString());
+      expect(identity.inactivityThreshold.toNumber()).to.equal(MIN_INACTIVITY_THRESHOLD);
+      expect(identity.lastOwnerActivity.toNumber()).to.be.at.least(previous.lastOwnerActivity.toNumber());
+    });
+
+    it("should only let the nominee start a succession claim", async () => {
+      const stranger = Keypair.generate();
+
+      try {
+        await program.methods
+          .startSuccession()
+          .accounts({
+            identityAccount: identityPda,
+            nominee: stranger.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("NotNominee");
+      }
+    });
+
+    it("should not start succession while the owner is active", async () => {
+      try {
+        await program.methods
+          .startSuccession()
+          .accounts({
+            identityAccount: identityPda,
+            nominee: nominee.publicKey,
+          })
+          .signers([nominee])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("IdentityNotInactive");
+      }
+
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.successionStartedAt.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("pseudonyms", () => {
+    let attester: Keypair;
+    let owner: Keypair;
+    let pseudonymPda: PublicKey;
+    const relyingParty = Keypair.generate().publicKey;
+
+    before(async () => {
+      attester = Keypair.generate();
+      owner = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        owner.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      // Commitment to the root identity; the salt never leaves the owner and attester
+      const rootIdentity = Keypair.generate().publicKey;
+      const commitment = crypto
+        .createHash("sha256")
+        .update(Buffer.concat([rootIdentity.toBuffer(), relyingParty.toBuffer(), generateHash()]))
+        .digest();
+
+      [pseudonymPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("pseudonym"), relyingParty.toBuffer(), commitment],
+        program.programId
+      );
+
+      await program.methods
+        .registerPseudonym(relyingParty, Array.from(commitment))
+        .accounts({
+          pseudonym: pseudonymPda,
+          owner: owner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .updateConfig({
+          verificationOracle: null,
+          credentialManager: null,
+          reputationEngine: null,
+          stakingManager: null,
+          minStakeAmount: null,
+          verificationFee: null,
+          tombstonePeriod: null,
+          complianceOfficer: null,
+          pseudonymAttester: attester.publicKey,
+        })
+        .accounts({
+          config: configPda,
+          admin: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+    });
+
+    it("should not answer for a pseudonym before it is attested", async () => {
+      try {
+        await program.methods
+          .assertPseudonym(new anchor.BN(0), new anchor.BN(0), new anchor.BN(3600))
+          .accounts({ pseudonym: pseudonymPda })
+          .view();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("PseudonymAttestationExpired");
+      }
+    });
+
+    it("should only accept attestations from the configured attester", async () => {
+      const stranger = Keypair.generate();
+
+      try {
+        await program.methods
+          .attestPseudonym(
+            { active: {} },
+            new anchor.BN(0b11),
+            new anchor.BN(900),
+            new anchor.BN(Math.floor(Date.now() / 1000) + 3600)
+          )
+          .accounts({
+            config: configPda,
+            pseudonym: pseudonymPda,
+            attester: stranger.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("UnauthorizedAttester");
+      }
+    });
+
+    it("should answer gating questions from the attestation alone", async () => {
+      await program.methods
+        .attestPseudonym(
+          { active: {} },
+          new anchor.BN(1 << 1),
+          new anchor.BN(600),
+          new anchor.BN(Math.floor(Date.now() / 1000) + 3600)
+        )
+        .accounts({
+          config: configPda,
+          pseudonym: pseudonymPda,
+          attester: attester.publicKey,
+        })
+        .signers([attester])
+        .rpc();
+
+      const assertion = await program.methods
+        .assertPseudonym(new anchor.BN(1 << 1), new anchor.BN(500), new anchor.BN(3600))
+        .accounts({ pseudonym: pseudonymPda })
+        .view();
+      expect(assertion.pseudonym.toString()).to.equal(pseudonymPda.toString());
+      expect(assertion.relyingParty.toString()).to.equal(relyingParty.toString());
+      expect(assertion.reputationScore.toNumber()).to.equal(600);
+
+      try {
+        await program.methods
+          .assertPseudonym(new anchor.BN(1 << 0), new anchor.BN(0), new anchor.BN(3600))
+          .accounts({ pseudonym: pseudonymPda })
+          .view();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("MissingRequiredVerification");
+      }
+    });
+  });
+
+  describe("migrate_identity", () => {
+    let victim: Keypair;
+    let attacker: Keypair;
+    let victimIdentityId: Uint8Array;
+
+    before(async () => {
+      victim = Keypair.generate();
+      attacker = Keypair.generate();
+
+      for (const keypair of [victim, attacker]) {
+        const signature = await provider.connection.requestAirdrop(
+          keypair.publicKey,
+          2 * anchor.web3.LAMPORTS_PER_SOL
+        );
+        await provider.connection.confirmTransaction(signature);
+      }
+
+      victimIdentityId = generateId();
+      const did = generateDID(victim.publicKey);
+      await program.methods
+        .createIdentity(Array.from(victimIdentityId), did, "https://example.com", [], 0)
+        .accounts({
+          identityAccount: findIdentityPDA(victimIdentityId, program.programId)[0],
+          authorityIndex: findAuthorityIndexPDA(victim.publicKey, program.programId)[0],
+          didIndex: findDidIndexPDA(did, program.programId)[0],
+          sponsorAccount: null,
+          authority: victim.publicKey,
+          payer: victim.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([victim])
+        .rpc();
+    });
+
+    it("should refuse to migrate an identity that already has the current layout", async () => {
+      // A current identity lives at ["identity", identity_id], which is also a valid legacy seed
+      const legacySeed = new PublicKey(victimIdentityId);
+      const newIdentityId = generateId();
+      const did = generateDID(attacker.publicKey);
+
+      try {
+        await program.methods
+          .migrateIdentity(legacySeed, Array.from(newIdentityId), did)
+          .accounts({
+            legacyIdentity: findIdentityPDA(victimIdentityId, program.programId)[0],
+            identityAccount: findIdentityPDA(newIdentityId, program.programId)[0],
+            authorityIndex: findAuthorityIndexPDA(attacker.publicKey, program.programId)[0],
+            didIndex: findDidIndexPDA(did, program.programId)[0],
+            authority: attacker.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([attacker])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("AccountNotFound");
+      }
+    });
+  });
+
+  // Successful updates only arrive through the oracle's finalize CPI, so this suite drives
+  // a verification request through two staked oracles
+  describe("update_verification_status", () => {
+    const AADHAAR = 0;
+
+    let user: Keypair;
+    let identityPda: PublicKey;
+    let oracles: Keypair[];
+
+    const [oracleConfigPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      oracleProgram.programId
+    );
+    const [oracleAuthorityPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("oracle_authority")],
+      oracleProgram.programId
+    );
+    const findOracleNode = (authority: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("oracle"), authority.toBuffer()],
+        oracleProgram.programId
+      )[0];
+    const findStakeAccount = (authority: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("stake"), authority.toBuffer()],
+        stakingProgram.programId
+      )[0];
+
+    before(async () => {
+      user = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        user.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      identityPda = await createIdentity(program, user);
+
+      await initializeStakingPool(stakingProgram, oracleProgram.programId);
+      await initializeOracleConfig(oracleProgram, program.programId, stakingProgram.programId);
+
+      oracles = [Keypair.generate(), Keypair.generate()];
+      for (const oracle of oracles) {
+        await provider.connection.confirmTransaction(
+          await provider.connection.requestAirdrop(oracle.publicKey, 5 * anchor.web3.LAMPORTS_PER_SOL)
+        );
+
+        await stakingProgram.methods
+          .stake(new anchor.BN(2 * anchor.web3.LAMPORTS_PER_SOL))
+          .accounts({
+            pool: PublicKey.findProgramAddressSync([Buffer.from("pool")], stakingProgram.programId)[0],
+            stakeAccount: findStakeAccount(oracle.publicKey),
+            poolVault: PublicKey.findProgramAddressSync([Buffer.from("vault")], stakingProgram.programId)[0],
+            owner: oracle.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([oracle])
+          .rpc();
+
+        await oracleProgram.methods
+          .registerOracle()
+          .accounts({
+            config: oracleConfigPda,
+            oracleNode: findOracleNode(oracle.publicKey),
+            stakeAccount: findStakeAccount(oracle.publicKey),
+            oracleAuthority: oracleAuthorityPda,
+            organization: null,
+            orgMember: null,
+            authority: oracle.publicKey,
+            stakingProgram: stakingProgram.programId,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([oracle])
+          .rpc();
+      }
+    });
+
+    // Leave the oracle suite with no active oracles
+    after(async () => {
+      for (const oracle of oracles) {
+        await oracleProgram.methods
+          .deregisterOracle()
+          .accounts({
+            config: oracleConfigPda,
+            oracleNode: findOracleNode(oracle.publicKey),
+            stakeAccount: findStakeAccount(oracle.publicKey),
+            oracleAuthority: oracleAuthorityPda,
+            authority: oracle.publicKey,
+            stakingProgram: stakingProgram.programId,
+          })
+          .signers([oracle])
+          .rpc();
+      }
+    });
+
+    it("should allow the configured verification oracle to update verification status", async () => {
+      const nonce = new anchor.BN(0);
+      const nullifier = Array.from(crypto.randomBytes(32));
+      const [requestPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("request"),
+          identityPda.toBuffer(),
+          Buffer.from([AADHAAR]),
+          nonce.toArrayLike(Buffer, "le", 8),
+        ],
+        oracleProgram.programId
+      );
+      const findResponse = (authority: PublicKey) =>
+        PublicKey.findProgramAddressSync(
+          [Buffer.from("response"), requestPda.toBuffer(), authority.toBuffer()],
+          oracleProgram.programId
+        )[0];
+
+      await oracleProgram.methods
+        .requestVerification(AADHAAR, Array.from(crypto.randomBytes(32)), nonce)
+        .accounts({
+          config: oracleConfigPda,
+          verificationRequest: requestPda,
+          identity: identityPda,
+          feeVault: PublicKey.findProgramAddressSync([Buffer.from("fee_vault")], oracleProgram.programId)[0],
+          requester: user.publicKey,
+          delegate: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+      for (const oracle of oracles) {
+        await oracleProgram.methods
+          .submitVerification(true, Array.from(crypto.randomBytes(32)), nullifier, 2)
+          .accounts({
+            config: oracleConfigPda,
+            oracleNode: findOracleNode(oracle.publicKey),
+            stakeAccount: findStakeAccount(oracle.publicKey),
+            verificationRequest: requestPda,
+            oracleResponse: findResponse(oracle.publicKey),
+            organization: null,
+            orgMember: null,
+            authority: oracle.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([oracle])
+          .rpc();
+      }
+
+      // The oracle program signs the registry update with its config PDA
+      await oracleProgram.methods
+        .finalizeVerification()
+        .accounts({
+          config: oracleConfigPda,
+          verificationRequest: requestPda,
+          identity: identityPda,
+          identityConfig: configPda,
+          oracleSigner: verificationOracle,
+          verificationRecord: PublicKey.findProgramAddressSync(
+            [Buffer.from("verification"), identityPda.toBuffer(), Buffer.from([AADHAAR])],
+            program.programId
+          )[0],
+          aadhaarNullifier: PublicKey.findProgramAddressSync(
+            [Buffer.from("nullifier"), Buffer.from(nullifier)],
+            program.programId
+          )[0],
+          identityRegistryProgram: program.programId,
+          finalizer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          oracles.flatMap((oracle) => [
+            { pubkey: findResponse(oracle.publicKey), isWritable: false, isSigner: false },
+            { pubkey: findOracleNode(oracle.publicKey), isWritable: true, isSigner: false },
+          ])
+        )
+        .rpc();
+
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.verificationBitmap.toNumber()).to.equal(1);
+    });
+
+    it("should reject unauthorized verification updates", async () => {
+      const unauthorizedOracle = Keypair.generate();
+      const [verificationRecordPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("verification"), identityPda.toBuffer(), Buffer.from([1])],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .updateVerificationStatus(1, true, 1, Array(32).fill(0))
+          .accounts({
+            identityAccount: identityPda,
+            oracle: unauthorizedOracle.publicKey,
+            config: configPda,
+            verificationRecord: verificationRecordPda,
+            sourceRequest: identityPda,
+            aadhaarNullifier: null,
+            payer: user.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([unauthorizedOracle, user])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("UnauthorizedOracle");
+      }
+    });
+  });
+
+  describe("add_recovery_key", () => {
+    let user: Keypair;
+    let identityPda: PublicKey;
+
+    before(async () => {
+      user = Keypair.generate();
+
+      const signature = await provider.connection.requestAirdrop(
+        user.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      // Create identity first
+      identityPda = await createIdentity(program, user);
+    });
+
+    it("should add a recovery key", async () => {
+      const newRecoveryKey = Keypair.generate().publicKey;
+
+      await program.methods
+        .addRecoveryKey(newRecoveryKey)
+        .accounts({
+          identityAccount: identityPda,
+          authority: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.recoveryKeys.length).to.equal(1);
+      expect(identity.recoveryKeys[0].key.toString()).to.equal(newRecoveryKey.toString());
+      expect(identity.recoveryThreshold).to.equal(1);
+    });
+  });
+
+  describe("recovery key management", () => {
+    let user: Keypair;
+    let originalKey: Keypair;
+    let addedKey: Keypair;
+    let identityPda: PublicKey;
+
+    before(async () => {
+      user = Keypair.generate();
+      originalKey = Keypair.generate();
+      addedKey = Keypair.generate();
+
+      for (const keypair of [user, addedKey]) {
+        const signature = await provider.connection.requestAirdrop(
+          keypair.publicKey,
+          2 * anchor.web3.LAMPORTS_PER_SOL
+        );
+        await provider.connection.confirmTransaction(signature);
+      }
+
+      identityPda = await createIdentity(program, user, [originalKey.publicKey]);
+    });
+
+    it("should hold a newly added recovery key in its cooldown", async () => {
+      await program.methods
+        .addRecoveryKey(addedKey.publicKey)
+        .accounts({
+          identityAccount: identityPda,
+          authority: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      const added = identity.recoveryKeys.find(
+        (entry: any) => entry.key.toString() === addedKey.publicKey.toString()
+      );
+      expect(added.activeAt.toNumber()).to.be.greaterThan(Math.floor(Date.now() / 1000));
+
+      const [recoveryProposalPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("recovery"), identityPda.toBuffer(), addedKey.publicKey.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .proposeRecovery(Keypair.generate().publicKey)
+          .accounts({
+            identityAccount: identityPda,
+            recoveryProposal: recoveryProposalPda,
+            recoverySigner: addedKey.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([addedKey])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("RecoveryKeyNotActive");
+      }
+    });
+
+    it("should replace and remove recovery keys", async () => {
+      const replacementKey = Keypair.generate().publicKey;
+
+      await program.methods
+        .replaceRecoveryKey(originalKey.publicKey, replacementKey)
+        .accounts({
+          identityAccount: identityPda,
+          authority: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      await program.methods
+        .removeRecoveryKey(replacementKey)
+        .accounts({
+          identityAccount: identityPda,
+          authority: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.recoveryKeys.map((entry: any) => entry.key.toString())).to.deep.equal([
+        addedKey.publicKey.toString(),
+      ]);
+      expect(identity.recoveryThreshold).to.equal(1);
+    });
+
+    it("should reject removing an unknown recovery key", async () => {
+      try {
+        await program.methods
+          .removeRecoveryKey(Keypair.generate().publicKey)
+          .accounts({
+            identityAccount: identityPda,
+            authority: user.publicKey,
+          })
+          .signers([user])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("RecoveryKeyNotFound");
+      }
+    });
+  });
+
+  describe("transfer_authority", () => {
+    let user: Keypair;
+    let newAuthority: Keypair;
+    let identityPda: PublicKey;
+    let didIndexPda: PublicKey;
+
+    before(async () => {
+      user = Keypair.generate();
+      newAuthority = Keypair.generate();
+
+      for (const keypair of [user, newAuthority]) {
+        const signature = await provider.connection.requestAirdrop(
+          keypair.publicKey,
+          2 * anchor.web3.LAMPORTS_PER_SOL
+        );
+        await provider.connection.confirmTransaction(signature);
+      }
+
+      identityPda = await createIdentity(program, user);
+      [didIndexPda] = findDidIndexPDA(generateDID(user.publicKey), program.programId);
+
+      await program.methods
+        .transferAuthority(newAuthority.publicKey)
+        .accounts({
+          identityAccount: identityPda,
+          authority: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    });
+
+    it("should only let the proposed authority accept", async () => {
+      const stranger = Keypair.generate();
+      const signature = await provider.connection.requestAirdrop(
+        stranger.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      try {
+        await program.methods
+          .acceptAuthority()
+          .accounts({
+            identityAccount: identityPda,
+            oldAuthorityIndex: findAuthorityIndexPDA(user.publicKey, program.programId)[0],
+            oldAuthority: user.publicKey,
+            newAuthorityIndex: findAuthorityIndexPDA(stranger.publicKey, program.programId)[0],
+            didIndex: didIndexPda,
+            newAuthority: stranger.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("NoPendingAuthorityTransfer");
+      }
+    });
+
+    it("should move the identity and its indexes to the new authority on acceptance", async () => {
+      const oldAuthorityIndexPda = findAuthorityIndexPDA(user.publicKey, program.programId)[0];
+      const newAuthorityIndexPda = findAuthorityIndexPDA(newAuthority.publicKey, program.programId)[0];
+
+      await program.methods
+        .acceptAuthority()
+        .accounts({
+          identityAccount: identityPda,
+          oldAuthorityIndex: oldAuthorityIndexPda,
+          oldAuthority: user.publicKey,
+          newAuthorityIndex: newAuthorityIndexPda,
+          didIndex: didIndexPda,
+          newAuthority: newAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([newAuthority])
+        .rpc();
+
+      const identity = await program.account.identityAccount.fetch(identityPda);
+      expect(identity.authority.toString()).to.equal(newAuthority.publicKey.toString());
+      expect(identity.pendingAuthority).to.be.null;
+
+      expect(await program.account.authorityIndex.fetchNullable(oldAuthorityIndexPda)).to.be.null;
+      const authorityIndex = await program.account.authorityIndex.fetch(newAuthorityIndexPda);
+      expect(authorityIndex.identity.toString()).to.equal(identityPda.toString());
+
+      const didIndex = await program.account.didIndex.fetch(didIndexPda);
+      expect(didIndex.authority.toString()).to.equal(newAuthority.publicKey.toString());
+    });
+  });
+
+  describe("recover_identity", () => {
+    let user: Keypair;
+    let recoveryKey: Keypair;
+    let newAuthority: PublicKey;
+    let identityPda: PublicKey;
+    let recoveryProposalPda: PublicKey;
+
+    before(async () => {
+      user = Keypair.generate();
+      recoveryKey = Keypair.generate();
+      newAuthority = Keypair.generate().publicKey;
+
+      const signature = await provider.connection.requestAirdrop(
+        user.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+
+      const signature2 = await provider.connection.requestAirdrop(
+        recoveryKey.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature2);
+
+      // Create identity with recovery key
+      identityPda = await createIdentity(program, user, [recoveryKey.publicKey]);
+
+      [recoveryProposalPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("recovery"), identityPda.toBuffer(), recoveryKey.publicKey.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("should open a recovery proposal to a new authority", async () => {
       await program.methods
-        .recoverIdentity(newAuthority)
+        .proposeRecovery(newAuthority)
         .accounts({
           identityAccount: identityPda,
+          recoveryProposal: recoveryProposalPda,
           recoverySigner: recoveryKey.publicKey,
+          systemProgram: SystemProgram.programId,
         })
         .signers([recoveryKey])
         .rpc();
 
+      const proposal = await program.account.recoveryProposal.fetch(recoveryProposalPda);
+      expect(proposal.newAuthority.toString()).to.equal(newAuthority.toString());
+      expect(proposal.approvals.length).to.equal(1);
+      expect(proposal.executableAt.toNumber() - proposal.createdAt.toNumber()).to.equal(48 * 60 * 60);
+    });
+
+    it("should not recover before the recovery delay has elapsed", async () => {
       const identity = await program.account.identityAccount.fetch(identityPda);
-      expect(identity.authority.toString()).to.equal(newAuthority.toString());
+
+      try {
+        await program.methods
+          .recoverIdentity()
+          .accounts({
+            identityAccount: identityPda,
+            recoveryProposal: recoveryProposalPda,
+            proposer: recoveryKey.publicKey,
+            oldAuthorityIndex: findAuthorityIndexPDA(user.publicKey, program.programId)[0],
+            oldAuthority: user.publicKey,
+            newAuthorityIndex: findAuthorityIndexPDA(newAuthority, program.programId)[0],
+            didIndex: findDidIndexPDA(identity.did, program.programId)[0],
+            payer: recoveryKey.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([recoveryKey])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("RecoveryDelayNotElapsed");
+      }
     });
 
     it("should reject unauthorized recovery attempt", async () => {
@@ -324,19 +2345,142 @@ describe("identity-registry", () => {
       );
       await provider.connection.confirmTransaction(signature);
 
+      const [unauthorizedProposalPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("recovery"), identityPda.toBuffer(), unauthorizedKey.publicKey.toBuffer()],
+        program.programId
+      );
+
       try {
         await program.methods
-          .recoverIdentity(Keypair.generate().publicKey)
+          .proposeRecovery(Keypair.generate().publicKey)
           .accounts({
             identityAccount: identityPda,
+            recoveryProposal: unauthorizedProposalPda,
             recoverySigner: unauthorizedKey.publicKey,
+            systemProgram: SystemProgram.programId,
           })
           .signers([unauthorizedKey])
           .rpc();
         expect.fail("Should have thrown an error");
       } catch (error: any) {
-        expect(error.error.errorCode.code).to.equal("ConstraintSeeds");
+        expect(error.error.errorCode.code).to.equal("UnauthorizedRecovery");
+      }
+    });
+  });
+
+  describe("approve_recovery", () => {
+    let user: Keypair;
+    let firstKey: Keypair;
+    let secondKey: Keypair;
+    let identityPda: PublicKey;
+    let recoveryProposalPda: PublicKey;
+
+    before(async () => {
+      user = Keypair.generate();
+      firstKey = Keypair.generate();
+      secondKey = Keypair.generate();
+
+      for (const keypair of [user, firstKey, secondKey]) {
+        const signature = await provider.connection.requestAirdrop(
+          keypair.publicKey,
+          2 * anchor.web3.LAMPORTS_PER_SOL
+        );
+        await provider.connection.confirmTransaction(signature);
+      }
+
+      identityPda = await createIdentity(program, user, [firstKey.publicKey, secondKey.publicKey]);
+
+      await program.methods
+        .setRecoveryThreshold(2)
+        .accounts({
+          identityAccount: identityPda,
+          authority: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      [recoveryProposalPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("recovery"), identityPda.toBuffer(), firstKey.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .proposeRecovery(Keypair.generate().publicKey)
+        .accounts({
+          identityAccount: identityPda,
+          recoveryProposal: recoveryProposalPda,
+          recoverySigner: firstKey.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([firstKey])
+        .rpc();
+    });
+
+    it("should reject a threshold above the number of recovery keys", async () => {
+      try {
+        await program.methods
+          .setRecoveryThreshold(3)
+          .accounts({
+            identityAccount: identityPda,
+            authority: user.publicKey,
+          })
+          .signers([user])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("InvalidRecoveryThreshold");
+      }
+    });
+
+    it("should count an approval from a second recovery key", async () => {
+      await program.methods
+        .approveRecovery()
+        .accounts({
+          identityAccount: identityPda,
+          recoveryProposal: recoveryProposalPda,
+          recoverySigner: secondKey.publicKey,
+        })
+        .signers([secondKey])
+        .rpc();
+
+      const proposal = await program.account.recoveryProposal.fetch(recoveryProposalPda);
+      expect(proposal.approvals.map((key: PublicKey) => key.toString())).to.have.members([
+        firstKey.publicKey.toString(),
+        secondKey.publicKey.toString(),
+      ]);
+    });
+
+    it("should reject a repeated approval", async () => {
+      try {
+        await program.methods
+          .approveRecovery()
+          .accounts({
+            identityAccount: identityPda,
+            recoveryProposal: recoveryProposalPda,
+            recoverySigner: secondKey.publicKey,
+          })
+          .signers([secondKey])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.equal("RecoveryAlreadyApproved");
       }
     });
+
+    it("should let the authority veto the recovery", async () => {
+      await program.methods
+        .cancelRecovery()
+        .accounts({
+          identityAccount: identityPda,
+          recoveryProposal: recoveryProposalPda,
+          proposer: firstKey.publicKey,
+          authority: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const proposal = await program.account.recoveryProposal.fetchNullable(recoveryProposalPda);
+      expect(proposal).to.be.null;
+    });
   });
 });
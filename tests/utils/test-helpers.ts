@@ -1,6 +1,10 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Keypair, PublicKey, Connection } from "@solana/web3.js";
+import { Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, Connection, SystemProgram } from "@solana/web3.js";
 import * as crypto from "crypto";
+import { IdentityRegistry } from "../../target/types/identity_registry";
+import { StakingManager } from "../../target/types/staking_manager";
+import { VerificationOracle } from "../../target/types/verification_oracle";
 
 /**
  * Shared test utilities for AadhaarChain Solana program tests
@@ -75,11 +79,37 @@ export function findIdentityConfigPDA(programId: PublicKey): [PublicKey, number]
  * Find PDA for identity account
  */
 export function findIdentityPDA(
+  identityId: Uint8Array,
+  programId: PublicKey
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("identity"), identityId],
+    programId
+  );
+}
+
+/**
+ * Find PDA mapping an authority to its identity
+ */
+export function findAuthorityIndexPDA(
   authority: PublicKey,
   programId: PublicKey
 ): [PublicKey, number] {
   return PublicKey.findProgramAddressSync(
-    [Buffer.from("identity"), authority.toBuffer()],
+    [Buffer.from("authority"), authority.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * Find PDA mapping a DID to its identity
+ */
+export function findDidIndexPDA(
+  did: string,
+  programId: PublicKey
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("did"), crypto.createHash("sha256").update(did).digest()],
     programId
   );
 }
@@ -193,6 +223,41 @@ export async function createFundedKeypair(
   return keypair;
 }
 
+/**
+ * Create an identity in the identity registry, paid for by its authority
+ */
+export async function createIdentity(
+  program: Program<IdentityRegistry>,
+  authority: Keypair,
+  recoveryKeys: PublicKey[] = [],
+  did: string = generateDID(authority.publicKey)
+): Promise<PublicKey> {
+  const identityId = generateId();
+  const [identityPda] = findIdentityPDA(identityId, program.programId);
+
+  await program.methods
+    .createIdentity(
+      Array.from(identityId),
+      did,
+      "https://example.com/metadata.json",
+      recoveryKeys,
+      recoveryKeys.length > 0 ? 1 : 0
+    )
+    .accounts({
+      identityAccount: identityPda,
+      authorityIndex: findAuthorityIndexPDA(authority.publicKey, program.programId)[0],
+      didIndex: findDidIndexPDA(did, program.programId)[0],
+      sponsorAccount: null,
+      authority: authority.publicKey,
+      payer: authority.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([authority])
+    .rpc();
+
+  return identityPda;
+}
+
 /**
  * Create multiple funded keypairs
  */
@@ -206,6 +271,157 @@ export async function createMultipleFundedKeypairs(
   return keypairs;
 }
 
+// ============== Shared Program State ==============
+
+// The registry config, the staking pool and the oracle config are program singletons on
+// the shared test validator. Every suite that needs them initializes them through these
+// helpers, so they are created once with the same admin and parameters whichever suite
+// runs first.
+
+/**
+ * Identity registry config admin and parameters
+ */
+export const REGISTRY_CONFIG = {
+  admin: Keypair.generate(),
+  credentialManager: Keypair.generate().publicKey,
+  reputationEngine: Keypair.generate().publicKey,
+  stakingManager: Keypair.generate().publicKey,
+  tombstonePeriod: 0, // Tombstones can be purged right away in tests
+};
+
+/**
+ * Staking pool admin and parameters
+ */
+export const STAKING_POOL = {
+  admin: Keypair.generate(),
+  minStake: 0.1 * anchor.web3.LAMPORTS_PER_SOL, // 0.1 SOL for testing
+  rewardRateBps: 500, // 5% APY
+  unstakeCooldown: 60, // 60 seconds for testing
+  identityRegistry: Keypair.generate().publicKey,
+};
+
+/**
+ * Verification oracle config admin and parameters
+ */
+export const ORACLE_CONFIG = {
+  admin: Keypair.generate(),
+  minOracleStake: 1 * anchor.web3.LAMPORTS_PER_SOL,
+  verificationFee: 0.01 * anchor.web3.LAMPORTS_PER_SOL,
+  requiredConfirmations: 2,
+  verificationTimeout: 3600, // 1 hour
+  slashPercentageBps: 1000, // 10%
+};
+
+let registryConfigInitialized: Promise<void> | null = null;
+let stakingPoolInitialized: Promise<void> | null = null;
+let oracleConfigInitialized: Promise<void> | null = null;
+
+/**
+ * Initialize the identity registry config once per test run. Verification results arrive
+ * by CPI signed with the oracle program's config PDA.
+ */
+export function initializeRegistryConfig(
+  program: Program<IdentityRegistry>,
+  oracleProgramId: PublicKey
+): Promise<void> {
+  if (!registryConfigInitialized) {
+    registryConfigInitialized = (async () => {
+      const [verificationOracle] = PublicKey.findProgramAddressSync(
+        [Buffer.from("config")],
+        oracleProgramId
+      );
+
+      await airdrop(program.provider.connection, REGISTRY_CONFIG.admin.publicKey);
+      await program.methods
+        .initializeConfig(
+          verificationOracle,
+          REGISTRY_CONFIG.credentialManager,
+          REGISTRY_CONFIG.reputationEngine,
+          REGISTRY_CONFIG.stakingManager,
+          new anchor.BN(REGISTRY_CONFIG.tombstonePeriod)
+        )
+        .accounts({
+          config: findIdentityConfigPDA(program.programId)[0],
+          admin: REGISTRY_CONFIG.admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([REGISTRY_CONFIG.admin])
+        .rpc();
+    })();
+  }
+  return registryConfigInitialized;
+}
+
+/**
+ * Initialize the staking pool once per test run. The oracle program locks and slashes
+ * stakes with its oracle_authority PDA.
+ */
+export function initializeStakingPool(
+  program: Program<StakingManager>,
+  oracleProgramId: PublicKey
+): Promise<void> {
+  if (!stakingPoolInitialized) {
+    stakingPoolInitialized = (async () => {
+      const [oracleAuthority] = PublicKey.findProgramAddressSync(
+        [Buffer.from("oracle_authority")],
+        oracleProgramId
+      );
+
+      await airdrop(program.provider.connection, STAKING_POOL.admin.publicKey);
+      await program.methods
+        .initializePool(
+          new anchor.BN(STAKING_POOL.minStake),
+          STAKING_POOL.rewardRateBps,
+          new anchor.BN(STAKING_POOL.unstakeCooldown),
+          STAKING_POOL.identityRegistry,
+          oracleAuthority
+        )
+        .accounts({
+          pool: findStakingPoolPDA(program.programId)[0],
+          poolVault: PublicKey.findProgramAddressSync([Buffer.from("vault")], program.programId)[0],
+          admin: STAKING_POOL.admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([STAKING_POOL.admin])
+        .rpc();
+    })();
+  }
+  return stakingPoolInitialized;
+}
+
+/**
+ * Initialize the verification oracle config once per test run
+ */
+export function initializeOracleConfig(
+  program: Program<VerificationOracle>,
+  identityRegistryProgramId: PublicKey,
+  stakingManagerProgramId: PublicKey
+): Promise<void> {
+  if (!oracleConfigInitialized) {
+    oracleConfigInitialized = (async () => {
+      await airdrop(program.provider.connection, ORACLE_CONFIG.admin.publicKey);
+      await program.methods
+        .initialize(
+          identityRegistryProgramId,
+          stakingManagerProgramId,
+          new anchor.BN(ORACLE_CONFIG.minOracleStake),
+          new anchor.BN(ORACLE_CONFIG.verificationFee),
+          ORACLE_CONFIG.requiredConfirmations,
+          new anchor.BN(ORACLE_CONFIG.verificationTimeout),
+          ORACLE_CONFIG.slashPercentageBps
+        )
+        .accounts({
+          config: PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId)[0],
+          admin: ORACLE_CONFIG.admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([ORACLE_CONFIG.admin])
+        .rpc();
+    })();
+  }
+  return oracleConfigInitialized;
+}
+
 // ============== Assertion Helpers ==============
 
 /**
@@ -356,3 +572,5 @@ export const MIN_REPUTATION_SCORE = 0;
 export const DECAY_RATE_BPS = 10; // 0.1% per day
 export const MIN_STAKE_AMOUNT = 1_000_000_000; // 1 SOL in lamports
 export const VERIFICATION_FEE = 10_000_000; // 0.01 SOL in lamports
+// Consent grant scope carried in the top bit of the verification mask
+export const CREDENTIAL_VERIFICATION_SCOPE = new anchor.BN(1).shln(63);